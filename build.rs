@@ -0,0 +1,57 @@
+//! Compresses every FIGlet and TOIlet font in `fonts/` into `OUT_DIR` and
+//! generates the table `src/fonts.rs` embeds, so new fonts only need to be
+//! dropped in.
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    let fonts_dir = Path::new("fonts");
+    println!("cargo:rerun-if-changed={}", fonts_dir.display());
+
+    let mut fonts: Vec<PathBuf> = fs::read_dir(fonts_dir)
+        .expect("fonts directory is readable")
+        .map(|entry| entry.expect("font entry is readable").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "flf" || ext == "tlf"))
+        .collect();
+    fonts.sort();
+
+    let mut names = HashSet::new();
+    let mut table = String::from("&[\n");
+    for path in &fonts {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .expect("font names are UTF-8");
+        if !names.insert(name) {
+            panic!("fonts/ has more than one font named '{}'", name);
+        }
+        let file_name = path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .expect("font names are UTF-8");
+        let content = fs::read(path).expect("font is readable");
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&content).expect("font compresses");
+        let compressed = encoder.finish().expect("font compresses");
+
+        let target = out_dir.join(format!("{}.deflate", file_name));
+        fs::write(&target, compressed).expect("compressed font is writable");
+        table.push_str(&format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            name,
+            target.display().to_string()
+        ));
+    }
+    table.push_str("]\n");
+
+    fs::write(out_dir.join("fonts.rs"), table).expect("font table is writable");
+}
@@ -2,31 +2,26 @@ use std::{thread, time::Duration};
 use crossterm::{
     cursor,
     terminal::{Clear, ClearType},
-    ExecutableCommand,
     QueueableCommand,
-    style::{Color, SetForegroundColor, Print, ResetColor},
 };
 use std::io::{stdout, Write};
 use rand::Rng;
 
+use crate::grid::{Cell, CellGrid, Rgb};
+
 pub struct AnimationConfig {
+    /// Milliseconds each frame stays on screen
     pub speed: u64,
     pub style: AnimationStyle,
-    pub color_mode: ColorMode,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum AnimationStyle {
     Type,
     Fade,
     Rainbow,
     Matrix,
-    None,
-}
-
-pub enum ColorMode {
-    Gradient(Vec<Color>),
-    Rainbow,
-    Static(Color),
+    #[value(skip)]
     None,
 }
 
@@ -35,11 +30,40 @@ impl Default for AnimationConfig {
         AnimationConfig {
             speed: 100,
             style: AnimationStyle::None,
-            color_mode: ColorMode::None,
         }
     }
 }
 
+/// One step of an animation and how long it is shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub grid: CellGrid,
+    pub delay: Duration,
+}
+
+/// Number of intermediate colors the fade passes through
+const FADE_STEPS: u32 = 5;
+
+/// Frames of color cycling in the rainbow animation
+const RAINBOW_FRAMES: usize = 50;
+
+/// Frames of falling glyphs in the matrix animation
+const MATRIX_FRAMES: usize = 15;
+
+const RAINBOW: [Rgb; 7] = [
+    Rgb { r: 255, g: 0, b: 0 },   // Red
+    Rgb { r: 255, g: 127, b: 0 }, // Orange
+    Rgb { r: 255, g: 255, b: 0 }, // Yellow
+    Rgb { r: 0, g: 255, b: 0 },   // Green
+    Rgb { r: 0, g: 0, b: 255 },   // Blue
+    Rgb { r: 75, g: 0, b: 130 },  // Indigo
+    Rgb { r: 148, g: 0, b: 211 }, // Violet
+];
+
+const MATRIX_GLYPHS: &str = "ﾊﾐﾋｰｳｼﾅﾓﾆｻﾜﾂｵﾘｱﾎﾃﾏｹﾒｴｶｷﾑﾕﾗｾﾈｽﾀﾇﾍ";
+const MATRIX_BRIGHT: Rgb = Rgb { r: 0, g: 255, b: 0 };
+const MATRIX_DIM: Rgb = Rgb { r: 0, g: 200, b: 0 };
+
 pub struct TextAnimator {
     config: AnimationConfig,
 }
@@ -49,116 +73,185 @@ impl TextAnimator {
         TextAnimator { config }
     }
 
-    pub fn animate(&self, text: &str) -> std::io::Result<()> {
-        match self.config.style {
-            AnimationStyle::Type => self.type_animation(text),
-            AnimationStyle::Fade => self.fade_animation(text),
-            AnimationStyle::Rainbow => self.rainbow_animation(text),
-            AnimationStyle::Matrix => self.matrix_animation(text),
-            AnimationStyle::None => {
-                print!("{}", text);
-                Ok(())
-            }
-        }
-    }
+    /// Build the frames of the animation for a rendered greeting. The last
+    /// frame is always the greeting itself, so every consumer (terminal,
+    /// recordings, exports) ends on the same picture.
+    pub fn frames(&self, grid: &CellGrid) -> Vec<Frame> {
+        let mut grids = match self.config.style {
+            AnimationStyle::Type => self.type_frames(grid),
+            AnimationStyle::Fade => self.fade_frames(grid),
+            AnimationStyle::Rainbow => self.rainbow_frames(grid),
+            AnimationStyle::Matrix => self.matrix_frames(grid),
+            AnimationStyle::None => Vec::new(),
+        };
+        grids.push(grid.clone());
 
-    fn type_animation(&self, text: &str) -> std::io::Result<()> {
-        let mut stdout = stdout();
-        for c in text.chars() {
-            stdout.queue(Print(c))?;
-            stdout.flush()?;
-            thread::sleep(Duration::from_millis(self.config.speed));
-        }
-        Ok(())
+        let delay = Duration::from_millis(self.config.speed);
+        grids
+            .into_iter()
+            .map(|grid| Frame { grid, delay })
+            .collect()
     }
 
-    fn fade_animation(&self, text: &str) -> std::io::Result<()> {
+    /// Play frames in place on the terminal
+    pub fn play(&self, frames: &[Frame]) -> std::io::Result<()> {
         let mut stdout = stdout();
-        let colors = [
-            Color::Rgb { r: 128, g: 128, b: 128 },
-            Color::Rgb { r: 160, g: 160, b: 160 },
-            Color::Rgb { r: 192, g: 192, b: 192 },
-            Color::Rgb { r: 224, g: 224, b: 224 },
-            Color::Rgb { r: 255, g: 255, b: 255 },
-        ];
-
-        for color in colors.iter() {
-            stdout.queue(cursor::SavePosition)?
-                .queue(SetForegroundColor(*color))?
-                .queue(Print(text))?
-                .queue(cursor::RestorePosition)?;
-            stdout.flush()?;
-            thread::sleep(Duration::from_millis(self.config.speed));
-        }
-        
-        stdout.queue(ResetColor)?;
-        stdout.queue(Print(text))?;
-        stdout.flush()?;
-        Ok(())
-    }
+        let mut previous_height = 0;
 
-    fn rainbow_animation(&self, text: &str) -> std::io::Result<()> {
-        let mut stdout = stdout();
-        let colors = [
-            Color::Rgb { r: 255, g: 0, b: 0 },    // Red
-            Color::Rgb { r: 255, g: 127, b: 0 },  // Orange
-            Color::Rgb { r: 255, g: 255, b: 0 },  // Yellow
-            Color::Rgb { r: 0, g: 255, b: 0 },    // Green
-            Color::Rgb { r: 0, g: 0, b: 255 },    // Blue
-            Color::Rgb { r: 75, g: 0, b: 130 },   // Indigo
-            Color::Rgb { r: 148, g: 0, b: 211 },  // Violet
-        ];
-
-        for _ in 0..50 {
-            for (i, c) in text.chars().enumerate() {
-                let color = colors[i % colors.len()];
-                stdout.queue(cursor::SavePosition)?
-                    .queue(SetForegroundColor(color))?
-                    .queue(Print(c))?;
+        for (index, frame) in frames.iter().enumerate() {
+            if previous_height > 0 {
+                stdout.queue(cursor::MoveUp(previous_height as u16))?;
+            }
+            for line in frame.grid.to_ansi_lines() {
+                stdout.queue(cursor::MoveToColumn(0))?
+                    .queue(Clear(ClearType::CurrentLine))?;
+                writeln!(stdout, "{}", line)?;
             }
             stdout.flush()?;
-            thread::sleep(Duration::from_millis(self.config.speed));
-            stdout.queue(cursor::RestorePosition)?;
-            
-            // Rotate colors
-            let first = colors[0];
-            for i in 0..colors.len()-1 {
-                colors[i] = colors[i+1];
+            previous_height = frame.grid.height();
+
+            if index + 1 < frames.len() {
+                thread::sleep(frame.delay);
             }
-            colors[colors.len()-1] = first;
         }
-        
-        stdout.queue(ResetColor)?;
-        stdout.queue(Print(text))?;
-        stdout.flush()?;
         Ok(())
     }
 
-    fn matrix_animation(&self, text: &str) -> std::io::Result<()> {
-        let mut stdout = stdout();
+    /// Reveal the art one column at a time, showing wide clusters whole
+    fn type_frames(&self, grid: &CellGrid) -> Vec<CellGrid> {
+        (1..grid.width())
+            .map(|shown| {
+                grid.map(|_, column, cell| {
+                    let start = if cell.is_continuation() { column.saturating_sub(1) } else { column };
+                    if start < shown {
+                        cell.clone()
+                    } else {
+                        Cell::blank()
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Fade the art in from the background color
+    fn fade_frames(&self, grid: &CellGrid) -> Vec<CellGrid> {
+        let background = grid.background.unwrap_or(Rgb::new(0, 0, 0));
+        (1..=FADE_STEPS)
+            .map(|step| {
+                let amount = step as f32 / (FADE_STEPS + 1) as f32;
+                grid.map(|_, _, cell| {
+                    let mut faded = cell.clone();
+                    let target = cell.style.fg.unwrap_or(Rgb::WHITE);
+                    faded.style.fg = Some(mix(background, target, amount));
+                    faded
+                })
+            })
+            .collect()
+    }
+
+    /// Cycle rainbow colors across the columns
+    fn rainbow_frames(&self, grid: &CellGrid) -> Vec<CellGrid> {
+        (0..RAINBOW_FRAMES)
+            .map(|frame| {
+                grid.map(|_, column, cell| {
+                    let mut colored = cell.clone();
+                    colored.style.fg = Some(RAINBOW[(column + frame) % RAINBOW.len()]);
+                    colored
+                })
+            })
+            .collect()
+    }
+
+    /// Flicker the art through random green glyphs
+    fn matrix_frames(&self, grid: &CellGrid) -> Vec<CellGrid> {
         let mut rng = rand::thread_rng();
-        let chars = "ﾊﾐﾋｰｳｼﾅﾓﾆｻﾜﾂｵﾘｱﾎﾃﾏｹﾒｴｶｷﾑﾕﾗｾﾈｽﾀﾇﾍ".chars().collect::<Vec<_>>();
-        
-        for _ in 0..15 {
-            stdout.queue(cursor::SavePosition)?;
-            for c in text.chars() {
-                if rng.gen::<f32>() < 0.5 {
-                    stdout.queue(SetForegroundColor(Color::Rgb { r: 0, g: 255, b: 0 }))?
-                        .queue(Print(chars[rng.gen_range(0..chars.len())]))?;
-                } else {
-                    stdout.queue(SetForegroundColor(Color::Rgb { r: 0, g: 200, b: 0 }))?
-                        .queue(Print(c))?;
-                }
-            }
-            stdout.flush()?;
-            thread::sleep(Duration::from_millis(self.config.speed));
-            stdout.queue(cursor::RestorePosition)?;
+        let glyphs = MATRIX_GLYPHS.chars().collect::<Vec<_>>();
+
+        (0..MATRIX_FRAMES)
+            .map(|_| {
+                grid.map(|_, _, cell| {
+                    if cell.is_blank() || cell.is_continuation() {
+                        return cell.clone();
+                    }
+                    let mut glyph = cell.clone();
+                    // Only narrow clusters swap, so every row keeps its width
+                    if rng.gen::<f32>() < 0.5 && cell.width() == 1 {
+                        glyph.symbol = glyphs[rng.gen_range(0..glyphs.len())].to_string();
+                        glyph.style.fg = Some(MATRIX_BRIGHT);
+                    } else {
+                        glyph.style.fg = Some(MATRIX_DIM);
+                    }
+                    glyph
+                })
+            })
+            .collect()
+    }
+}
+
+/// Blend two colors, `amount` of the way from `from` to `to`
+pub fn mix(from: Rgb, to: Rgb, amount: f32) -> Rgb {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    Rgb::new(channel(from.r, to.r), channel(from.g, to.g), channel(from.b, to.b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::CellStyle;
+
+    fn animator(style: AnimationStyle) -> TextAnimator {
+        TextAnimator::new(AnimationConfig { speed: 40, style })
+    }
+
+    #[test]
+    fn test_frames_end_on_the_greeting() {
+        let grid = CellGrid::from_lines(&["abc", "de"], CellStyle::fg(Rgb::WHITE));
+        for style in [
+            AnimationStyle::Type,
+            AnimationStyle::Fade,
+            AnimationStyle::Rainbow,
+            AnimationStyle::Matrix,
+            AnimationStyle::None,
+        ] {
+            let frames = animator(style).frames(&grid);
+            assert_eq!(frames.last().unwrap().grid, grid);
+            assert!(frames.iter().all(|f| f.delay == Duration::from_millis(40)));
         }
-        
-        stdout.queue(SetForegroundColor(Color::Rgb { r: 0, g: 255, b: 0 }))?
-            .queue(Print(text))?
-            .queue(ResetColor)?;
-        stdout.flush()?;
-        Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_type_reveals_columns() {
+        let grid = CellGrid::from_lines(&["abc"], CellStyle::default());
+        let frames = animator(AnimationStyle::Type).frames(&grid);
+        let text: Vec<String> = frames
+            .iter()
+            .map(|f| f.grid.rows()[0].iter().map(|c| c.symbol.as_str()).collect())
+            .collect();
+        assert_eq!(text, vec!["a  ", "ab ", "abc"]);
+    }
+
+    #[test]
+    fn test_wide_clusters_keep_their_columns() {
+        let grid = CellGrid::from_lines(&["日a"], CellStyle::fg(Rgb::WHITE));
+        let frames = animator(AnimationStyle::Type).frames(&grid);
+        let text: Vec<String> = frames
+            .iter()
+            .map(|f| f.grid.rows()[0].iter().map(|c| c.symbol.as_str()).collect())
+            .collect();
+        assert_eq!(text, vec!["日 ", "日 ", "日a"]);
+
+        for frame in animator(AnimationStyle::Matrix).frames(&grid) {
+            assert_eq!(frame.grid.rows()[0][0].symbol, "日");
+            assert!(frame.grid.rows()[0][1].is_continuation());
+        }
+    }
+
+    #[test]
+    fn test_fade_starts_near_background() {
+        let mut grid = CellGrid::from_lines(&["a"], CellStyle::fg(Rgb::WHITE));
+        grid.background = Some(Rgb::new(0, 0, 0));
+        let frames = animator(AnimationStyle::Fade).frames(&grid);
+        assert_eq!(frames.len(), FADE_STEPS as usize + 1);
+        assert_eq!(frames[0].grid.rows()[0][0].style.fg, Some(Rgb::new(43, 43, 43)));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::bitmap::PixelMode;
+use crate::figlet::LayoutMode;
+use crate::grid::CellGrid;
+
+/// How a font file is drawn, for the kinds of font each option applies to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FontOptions {
+    /// FIGlet layout, or the font's own when unset
+    pub layout: Option<LayoutMode>,
+    /// How bitmap font pixels become cells
+    pub pixel: PixelMode,
+}
+
+/// Measurements of a font, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontMetrics {
+    /// Rows in one line of rendered text
+    pub height: usize,
+}
+
+/// Turns text into cells for the renderer. Cells left in the default style
+/// take the theme's art style; a backend may color cells itself.
+pub trait FontBackend {
+    /// Name the font was selected by
+    fn name(&self) -> &str;
+
+    fn render(&self, text: &str) -> CellGrid;
+
+    fn metrics(&self) -> FontMetrics;
+
+    /// Whether the font has its own glyph for a character
+    fn supports(&self, ch: char) -> bool;
+}
@@ -0,0 +1,550 @@
+//! Bitmap fonts (BDF and the Linux console's PSF) drawn with block and
+//! braille characters.
+
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+
+use crate::backend::{FontBackend, FontMetrics};
+use crate::grid::{CellGrid, CellStyle};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// PSF1 mode bits: 512 glyphs, and a Unicode table after the glyphs
+const PSF1_MODE512: u8 = 0x01;
+const PSF1_MODEHASTAB: u8 = 0x02;
+const PSF1_MODESEQ: u8 = 0x04;
+
+/// PSF2 flag for a Unicode table after the glyphs
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+
+/// Quadrant blocks indexed by their lit corners: top left, top right,
+/// bottom left, bottom right as bits 0 to 3
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Braille dot bits for the pixel at each (column, row) of a 2x4 cell
+const BRAILLE_DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// How many pixels one character cell draws
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PixelMode {
+    /// One pixel as two full blocks, keeping pixels square
+    Full,
+    /// Two pixels stacked in half blocks
+    #[default]
+    Half,
+    /// Four pixels in quadrant blocks
+    Quadrant,
+    /// Eight pixels as braille dots
+    Braille,
+}
+
+impl PixelMode {
+    /// Pixel columns and rows covered by one cell (full blocks use two
+    /// cells per pixel)
+    fn cell_pixels(self) -> (usize, usize) {
+        match self {
+            PixelMode::Full => (1, 1),
+            PixelMode::Half => (1, 2),
+            PixelMode::Quadrant => (2, 2),
+            PixelMode::Braille => (2, 4),
+        }
+    }
+}
+
+type Pixels = Vec<Vec<bool>>;
+
+/// A glyph's pixels, `height` rows of its advance width
+#[derive(Debug, Clone)]
+struct Glyph {
+    rows: Pixels,
+}
+
+/// A parsed BDF or PSF font
+#[derive(Debug, Clone)]
+pub struct BitmapFont {
+    height: usize,
+    glyphs: HashMap<char, Glyph>,
+    /// Copyright or name from a BDF font's properties
+    description: Option<String>,
+}
+
+impl BitmapFont {
+    /// Parse a PSF1 or PSF2 font, gzipped or not, or a BDF font
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            let mut unpacked = Vec::new();
+            GzDecoder::new(bytes)
+                .read_to_end(&mut unpacked)
+                .context("Failed to decompress font")?;
+            return Self::parse(&unpacked);
+        }
+        if bytes.starts_with(&PSF1_MAGIC) {
+            return parse_psf1(bytes);
+        }
+        if bytes.starts_with(&PSF2_MAGIC) {
+            return parse_psf2(bytes);
+        }
+        let text = std::str::from_utf8(bytes).context("Not a BDF or PSF font")?;
+        parse_bdf(text)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Pixels of one line of text, or `None` when the font has none of its
+    /// characters
+    fn rasterize(&self, line: &str) -> Option<Pixels> {
+        let mut pixels: Pixels = vec![Vec::new(); self.height];
+        let mut rendered = false;
+        for glyph in line.chars().filter_map(|ch| self.glyphs.get(&ch)) {
+            for (row, glyph_row) in pixels.iter_mut().zip(&glyph.rows) {
+                row.extend(glyph_row);
+            }
+            rendered = true;
+        }
+        rendered.then_some(pixels)
+    }
+}
+
+/// Draws a bitmap font with one of the pixel modes
+pub struct BitmapBackend {
+    name: String,
+    font: BitmapFont,
+    pixel: PixelMode,
+}
+
+impl BitmapBackend {
+    pub fn new(name: &str, font: BitmapFont, pixel: PixelMode) -> Self {
+        Self {
+            name: name.to_string(),
+            font,
+            pixel,
+        }
+    }
+}
+
+impl FontBackend for BitmapBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn render(&self, text: &str) -> CellGrid {
+        let mut lines: Vec<String> = Vec::new();
+        for line in text.lines() {
+            match self.font.rasterize(line) {
+                Some(pixels) => lines.extend(draw(&pixels, self.pixel)),
+                // Blank lines separate, and lines without glyphs stay plain
+                None if line.trim().is_empty() => lines.push(String::new()),
+                None => lines.push(line.to_string()),
+            }
+        }
+        CellGrid::from_lines(&lines, CellStyle::default())
+    }
+
+    fn metrics(&self) -> FontMetrics {
+        let (_, rows) = self.pixel.cell_pixels();
+        FontMetrics {
+            height: self.font.height.div_ceil(rows),
+        }
+    }
+
+    fn supports(&self, ch: char) -> bool {
+        self.font.glyphs.contains_key(&ch)
+    }
+}
+
+/// Turn pixels into rows of block or braille characters
+fn draw(pixels: &Pixels, mode: PixelMode) -> Vec<String> {
+    let (columns, rows) = mode.cell_pixels();
+    let width = pixels.first().map_or(0, Vec::len);
+    let lit = |x: usize, y: usize| {
+        pixels
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(false)
+    };
+
+    (0..pixels.len())
+        .step_by(rows)
+        .map(|y| {
+            (0..width)
+                .step_by(columns)
+                .map(|x| match mode {
+                    PixelMode::Full => if lit(x, y) { "██" } else { "  " }.to_string(),
+                    PixelMode::Half => match (lit(x, y), lit(x, y + 1)) {
+                        (false, false) => " ",
+                        (true, false) => "▀",
+                        (false, true) => "▄",
+                        (true, true) => "█",
+                    }
+                    .to_string(),
+                    PixelMode::Quadrant => {
+                        let corners = [(0, 0), (1, 0), (0, 1), (1, 1)];
+                        let index = corners
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, (dx, dy))| lit(x + dx, y + dy))
+                            .fold(0, |index, (bit, _)| index | 1 << bit);
+                        QUADRANTS[index].to_string()
+                    }
+                    PixelMode::Braille => braille(|dx, dy| lit(x + dx, y + dy)).to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Braille pattern with a dot for each lit pixel of a 2x4 block, given by
+/// column and row. An empty pattern is a plain space.
+pub fn braille(lit: impl Fn(usize, usize) -> bool) -> char {
+    let mut dots = 0;
+    for (dx, column) in BRAILLE_DOTS.iter().enumerate() {
+        for (dy, dot) in column.iter().enumerate() {
+            if lit(dx, dy) {
+                dots |= dot;
+            }
+        }
+    }
+    match dots {
+        0 => ' ',
+        _ => char::from_u32(0x2800 + dots).unwrap_or(' '),
+    }
+}
+
+/// Unpack `height` rows of `width` pixels, each row padded to whole bytes
+fn unpack(bitmap: &[u8], width: usize, height: usize) -> Pixels {
+    let row_bytes = width.div_ceil(8);
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    bitmap
+                        .get(y * row_bytes + x / 8)
+                        .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn parse_psf1(bytes: &[u8]) -> Result<BitmapFont> {
+    let truncated = || anyhow!("PSF font is truncated");
+    let mode = *bytes.get(2).ok_or_else(truncated)?;
+    let height = *bytes.get(3).ok_or_else(truncated)? as usize;
+    let count = if mode & PSF1_MODE512 != 0 { 512 } else { 256 };
+    let glyph_data = bytes.get(4..4 + count * height).ok_or_else(truncated)?;
+    let glyphs: Vec<Glyph> = glyph_data
+        .chunks(height.max(1))
+        .map(|bitmap| Glyph {
+            rows: unpack(bitmap, 8, height),
+        })
+        .collect();
+
+    let mut font = BitmapFont {
+        height,
+        glyphs: HashMap::new(),
+        description: None,
+    };
+    if mode & (PSF1_MODEHASTAB | PSF1_MODESEQ) == 0 {
+        // Without a table, glyphs are in code point order
+        for (code, glyph) in (0u32..).zip(glyphs) {
+            if let Some(ch) = char::from_u32(code) {
+                font.glyphs.insert(ch, glyph);
+            }
+        }
+        return Ok(font);
+    }
+
+    // Per glyph: UCS-2 code points, then sequences after 0xFFFE, ending at 0xFFFF
+    let table = &bytes[4 + count * height..];
+    let mut values = table
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+    for glyph in glyphs {
+        let mut in_sequence = false;
+        for value in values.by_ref() {
+            match value {
+                0xFFFF => break,
+                0xFFFE => in_sequence = true,
+                code if !in_sequence => {
+                    if let Some(ch) = char::from_u32(code as u32) {
+                        font.glyphs.insert(ch, glyph.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(font)
+}
+
+fn parse_psf2(bytes: &[u8]) -> Result<BitmapFont> {
+    let field = |index: usize| -> Result<usize> {
+        let offset = 4 + index * 4;
+        let value = bytes
+            .get(offset..offset + 4)
+            .ok_or_else(|| anyhow!("PSF font is truncated"))?;
+        Ok(u32::from_le_bytes([value[0], value[1], value[2], value[3]]) as usize)
+    };
+    let header_size = field(1)?;
+    let flags = field(2)? as u32;
+    let count = field(3)?;
+    let glyph_size = field(4)?;
+    let height = field(5)?;
+    let width = field(6)?;
+
+    let glyphs_end = header_size + count * glyph_size;
+    let glyph_data = bytes
+        .get(header_size..glyphs_end)
+        .ok_or_else(|| anyhow!("PSF font is truncated"))?;
+    let glyphs: Vec<Glyph> = glyph_data
+        .chunks(glyph_size.max(1))
+        .map(|bitmap| Glyph {
+            rows: unpack(bitmap, width, height),
+        })
+        .collect();
+
+    let mut font = BitmapFont {
+        height,
+        glyphs: HashMap::new(),
+        description: None,
+    };
+    if flags & PSF2_HAS_UNICODE_TABLE == 0 {
+        for (code, glyph) in (0u32..).zip(glyphs) {
+            if let Some(ch) = char::from_u32(code) {
+                font.glyphs.insert(ch, glyph);
+            }
+        }
+        return Ok(font);
+    }
+
+    // Per glyph: UTF-8 characters, then sequences after 0xFE, ending at 0xFF
+    let mut entries = bytes[glyphs_end..].split(|&byte| byte == 0xFF);
+    for glyph in glyphs {
+        let Some(entry) = entries.next() else {
+            break;
+        };
+        let single = entry.split(|&byte| byte == 0xFE).next().unwrap_or_default();
+        for ch in String::from_utf8_lossy(single).chars() {
+            if ch != char::REPLACEMENT_CHARACTER {
+                font.glyphs.insert(ch, glyph.clone());
+            }
+        }
+    }
+    Ok(font)
+}
+
+fn parse_bdf(text: &str) -> Result<BitmapFont> {
+    let mut lines = text.lines().map(str::trim);
+    if !lines.next().unwrap_or_default().starts_with("STARTFONT") {
+        return Err(anyhow!("Not a BDF or PSF font"));
+    }
+
+    let numbers = |rest: &str| -> Vec<i32> {
+        rest.split_whitespace()
+            .filter_map(|value| value.parse().ok())
+            .collect()
+    };
+    let mut bounding_box = None;
+    let (mut ascent, mut descent) = (None, None);
+    let (mut name, mut copyright) = (None, None);
+    let mut characters: Vec<(u32, i32, [i32; 4], Vec<u64>)> = Vec::new();
+
+    while let Some(line) = lines.next() {
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "FONT" => name = Some(rest.to_string()),
+            "COPYRIGHT" => copyright = Some(rest.trim_matches('"').to_string()),
+            "FONTBOUNDINGBOX" => bounding_box = <[i32; 4]>::try_from(numbers(rest)).ok(),
+            "FONT_ASCENT" => ascent = numbers(rest).first().copied(),
+            "FONT_DESCENT" => descent = numbers(rest).first().copied(),
+            "STARTCHAR" => {
+                let (mut code, mut advance, mut bbx) = (None, None, None);
+                let mut bitmap = Vec::new();
+                while let Some(line) = lines.next() {
+                    let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+                    match keyword {
+                        "ENCODING" => code = numbers(rest).first().copied(),
+                        "DWIDTH" => advance = numbers(rest).first().copied(),
+                        "BBX" => bbx = <[i32; 4]>::try_from(numbers(rest)).ok(),
+                        "BITMAP" => {
+                            for row in lines.by_ref() {
+                                if row == "ENDCHAR" {
+                                    break;
+                                }
+                                // Rows are whole bytes; align them to the left of a u64
+                                let bits = row.len() * 4;
+                                let value = u64::from_str_radix(row, 16)
+                                    .with_context(|| format!("Invalid BDF bitmap row '{}'", row))?;
+                                bitmap.push(value << (64 - bits.min(64)));
+                            }
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if let (Some(code), Some(bbx)) = (code, bbx.or(bounding_box)) {
+                    if let Ok(code) = u32::try_from(code) {
+                        characters.push((code, advance.unwrap_or(bbx[0] + bbx[2]), bbx, bitmap));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let [_, box_height, _, box_offset] =
+        bounding_box.ok_or_else(|| anyhow!("BDF font has no FONTBOUNDINGBOX"))?;
+    let ascent = ascent.unwrap_or(box_height + box_offset);
+    let descent = descent.unwrap_or(-box_offset);
+    let height = (ascent + descent).max(0) as usize;
+
+    let mut glyphs = HashMap::new();
+    for (code, advance, [width, rows, x_offset, y_offset], bitmap) in characters {
+        let Some(ch) = char::from_u32(code) else {
+            continue;
+        };
+        let advance = advance.max(0) as usize;
+        let mut pixels = vec![vec![false; advance]; height];
+        // Rows count down from the top of the glyph's box, which sits
+        // `y_offset` above the baseline
+        let top = ascent - y_offset - rows;
+        for (row, bits) in bitmap.iter().enumerate() {
+            for column in 0..width {
+                let (x, y) = (x_offset + column, top + row as i32);
+                if bits & (1 << (63 - column.min(63))) == 0 || x < 0 || y < 0 {
+                    continue;
+                }
+                if let Some(pixel) = pixels
+                    .get_mut(y as usize)
+                    .and_then(|r| r.get_mut(x as usize))
+                {
+                    *pixel = true;
+                }
+            }
+        }
+        glyphs.insert(ch, Glyph { rows: pixels });
+    }
+
+    Ok(BitmapFont {
+        height,
+        glyphs,
+        description: copyright.or(name),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 checker 'x' and a 2x2 solid block for every other character
+    const BDF: &str = "STARTFONT 2.1
+FONT -test-tiny
+FONTBOUNDINGBOX 2 2 0 0
+STARTPROPERTIES 1
+COPYRIGHT \"Public domain\"
+ENDPROPERTIES
+CHARS 2
+STARTCHAR x
+ENCODING 120
+DWIDTH 2 0
+BBX 2 2 0 0
+BITMAP
+80
+40
+ENDCHAR
+STARTCHAR ee
+ENCODING 235
+DWIDTH 3 0
+BBX 2 1 1 0
+BITMAP
+C0
+ENDCHAR
+ENDFONT
+";
+
+    fn backend(pixel: PixelMode) -> BitmapBackend {
+        BitmapBackend::new("tiny", BitmapFont::parse(BDF.as_bytes()).unwrap(), pixel)
+    }
+
+    fn rows(grid: &CellGrid) -> Vec<String> {
+        grid.rows()
+            .iter()
+            .map(|row| row.iter().map(|c| c.symbol.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_bdf_glyphs_sit_on_the_baseline() {
+        let font = BitmapFont::parse(BDF.as_bytes()).unwrap();
+        assert_eq!(font.description(), Some("Public domain"));
+        assert_eq!(font.height, 2);
+
+        let pixels = font.rasterize("xë").unwrap();
+        let drawn: Vec<String> = pixels
+            .iter()
+            .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+            .collect();
+        assert_eq!(drawn, vec!["#....", ".#.##"]);
+        assert!(font.rasterize("?").is_none());
+    }
+
+    #[test]
+    fn test_pixel_modes() {
+        assert_eq!(
+            rows(&backend(PixelMode::Full).render("x")),
+            vec!["██  ", "  ██"]
+        );
+        assert_eq!(rows(&backend(PixelMode::Half).render("x")), vec!["▀▄"]);
+        assert_eq!(rows(&backend(PixelMode::Quadrant).render("x")), vec!["▚"]);
+        assert_eq!(
+            rows(&backend(PixelMode::Braille).render("x")),
+            vec!["\u{2811}"]
+        );
+        assert_eq!(
+            rows(&backend(PixelMode::Half).render("x\n\n?")),
+            vec!["▀▄", "", "?"]
+        );
+        assert_eq!(backend(PixelMode::Braille).metrics().height, 1);
+        assert_eq!(backend(PixelMode::Full).metrics().height, 2);
+    }
+
+    #[test]
+    fn test_psf_fonts_with_unicode_tables() {
+        // PSF2: two 3x2 glyphs; the first is 'A' and 'Б', the second 'z'
+        let mut psf2 = PSF2_MAGIC.to_vec();
+        for value in [0u32, 32, PSF2_HAS_UNICODE_TABLE, 2, 2, 2, 3] {
+            psf2.extend(value.to_le_bytes());
+        }
+        psf2.extend([0xE0, 0xA0, 0x40, 0x40]);
+        psf2.extend("AБ".as_bytes());
+        psf2.extend([0xFF, b'z', 0xFE, b'z', b'z', 0xFF]);
+        let font = BitmapFont::parse(&psf2).unwrap();
+        assert_eq!(
+            draw(&font.rasterize("Бz").unwrap(), PixelMode::Half),
+            vec!["█▀█ █ "]
+        );
+        assert!(font.glyphs.contains_key(&'A'));
+
+        // PSF1, gzipped, without a table: glyphs follow code points
+        let mut psf1 = vec![0x36, 0x04, 0, 1];
+        psf1.extend((0..=255u8).map(|code| if code == b'!' { 0xFF } else { 0 }));
+        let mut gzipped = Vec::new();
+        let mut encoder = flate2::write::GzEncoder::new(&mut gzipped, flate2::Compression::fast());
+        std::io::Write::write_all(&mut encoder, &psf1).unwrap();
+        encoder.finish().unwrap();
+        let font = BitmapFont::parse(&gzipped).unwrap();
+        assert_eq!(
+            draw(&font.rasterize("!").unwrap(), PixelMode::Half),
+            vec!["▀".repeat(8)]
+        );
+    }
+}
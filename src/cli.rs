@@ -1,36 +1,98 @@
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
-use std::io::{self, IsTerminal};
+use clap::{ArgGroup, Parser, Subcommand};
+use regex::Regex;
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 
-use crate::config::ConfigManager;
-use crate::render::Renderer;
-use crate::themes::ThemeManager;
+use crate::animation::{AnimationConfig, AnimationStyle, TextAnimator};
+use crate::backend::{FontBackend, FontOptions};
+use crate::bitmap::PixelMode;
+use crate::config::{Config, ConfigManager};
+use crate::export::{self, cast, Document, ExportFormat};
+use crate::fallback::FallbackBackend;
+use crate::figlet::LayoutMode;
+use crate::fonts;
+use crate::graphics::{self, ImageProtocol, Overlay, Protocol};
+use crate::greetings::{self, Greeting, RotationMode, RotationState};
+use crate::grid::{CellGrid, CellStyle};
+use crate::motd;
+use crate::picture::{self, Dither, ImageMode, ImageOptions, ImagePosition};
+use crate::quotes::{self, QuoteFilter};
+use crate::render::{terminal_height, terminal_width, Renderer};
+use crate::sysinfo;
+use crate::themes::{self, ThemeManager};
+use crate::width;
 
 #[derive(Parser, Debug)]
 #[command(name = "greetme")]
 #[command(version = "1.0.0")]
 #[command(about = "A fast, themeable terminal greeting application", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(group(
+    ArgGroup::new("legacy_action")
+        .args(["stdin", "text", "file", "read", "create_config", "set_theme", "list_themes", "preview_theme"])
+        .multiple(false)
+))]
 pub struct Args {
-    /// Text to display as greeting
-    #[arg(short = 't', long = "text")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Enable verbose output
+    #[arg(short = 'v', long = "verbose", global = true)]
+    pub verbose: bool,
+
+    /// Pass - to read greeting text from stdin (deprecated: use 'greetme show -')
+    #[arg(value_name = "-", value_parser = ["-"], hide_possible_values = true)]
+    pub stdin: Option<String>,
+
+    /// Text to display as greeting (deprecated: use 'greetme show')
+    #[arg(short = 't', long = "text", help_heading = "Deprecated options")]
     pub text: Option<String>,
 
-    /// Read and display stored greeting from config
-    #[arg(short = 'r', long = "read")]
+    /// Read greeting text from a file (deprecated: use 'greetme show --file')
+    #[arg(long = "file", value_name = "PATH", help_heading = "Deprecated options")]
+    pub file: Option<PathBuf>,
+
+    /// Maximum number of characters accepted from stdin or --file
+    #[arg(long = "max-input-length", value_name = "N", help_heading = "Deprecated options")]
+    pub max_input_length: Option<usize>,
+
+    /// Read and display stored greeting from config (deprecated: use 'greetme read')
+    #[arg(short = 'r', long = "read", help_heading = "Deprecated options")]
     pub read: bool,
 
-    /// Create default config and themes
-    #[arg(short = 'c', long = "create-config")]
+    /// Rotation mode to use with -r, overriding the config
+    #[arg(long = "rotation", value_enum, help_heading = "Deprecated options")]
+    pub rotation: Option<RotationMode>,
+
+    /// Create default config and themes (deprecated: use 'greetme init')
+    #[arg(short = 'c', long = "create-config", help_heading = "Deprecated options")]
     pub create_config: bool,
 
-    /// Set the active theme
-    #[arg(short = 's', long = "set-theme")]
+    /// Set the active theme (deprecated: use 'greetme theme set')
+    #[arg(short = 's', long = "set-theme", help_heading = "Deprecated options")]
     pub set_theme: Option<String>,
 
-    /// List available themes
-    #[arg(long = "list-themes")]
+    /// List available themes (deprecated: use 'greetme theme list')
+    #[arg(long = "list-themes", help_heading = "Deprecated options")]
     pub list_themes: bool,
 
+    /// Preview a theme (deprecated: use 'greetme theme preview')
+    #[arg(long = "preview-theme", help_heading = "Deprecated options")]
+    pub preview_theme: Option<String>,
+
+    /// Save current settings to config (use with -t)
+    #[arg(long = "save", help_heading = "Deprecated options")]
+    pub save: bool,
+
+    #[command(flatten, next_help_heading = "Deprecated options")]
+    pub display: DisplayArgs,
+}
+
+/// Options shared by every command that renders a greeting
+#[derive(clap::Args, Debug, Clone)]
+pub struct DisplayArgs {
     /// Use specified font for this run
     #[arg(long = "font")]
     pub font: Option<String>,
@@ -39,58 +101,610 @@ pub struct Args {
     #[arg(long = "size", default_value = "1.0")]
     pub size: f32,
 
-    /// Save current settings to config (use with -t)
+    /// How FIGlet characters are fitted together (defaults to the font's own layout)
+    #[arg(long = "layout", value_enum, value_name = "MODE")]
+    pub layout: Option<LayoutMode>,
+
+    /// How bitmap font pixels are drawn
+    #[arg(long = "pixel", value_enum, value_name = "MODE", default_value_t = PixelMode::Half)]
+    pub pixel: PixelMode,
+
+    /// Show a random fortune-style quote beneath the greeting
+    #[arg(long = "quote")]
+    pub quote: bool,
+
+    /// Fortune file or directory to pick quotes from (defaults to the system fortune database)
+    #[arg(long = "quote-file", value_name = "PATH", requires = "quote")]
+    pub quote_file: Option<PathBuf>,
+
+    /// Only pick quotes up to this many characters long
+    #[arg(long = "quote-max-length", value_name = "N", requires = "quote")]
+    pub quote_max_length: Option<usize>,
+
+    /// Only pick quotes matching this regular expression
+    #[arg(long = "quote-match", value_name = "REGEX", requires = "quote")]
+    pub quote_match: Option<String>,
+
+    /// Show a system information panel beside the greeting
+    #[arg(long = "info")]
+    pub info: bool,
+
+    /// Draw an image, such as a logo, with the greeting
+    #[arg(long = "image", value_name = "PATH")]
+    pub image: Option<PathBuf>,
+
+    /// How the image is drawn
+    #[arg(
+        long = "image-mode",
+        value_enum,
+        value_name = "MODE",
+        default_value_t = ImageMode::Blocks,
+        requires = "image"
+    )]
+    pub image_mode: ImageMode,
+
+    /// Dithering for the ascii and braille image modes
+    #[arg(long = "dither", value_enum, default_value_t = Dither::None, requires = "image")]
+    pub dither: Dither,
+
+    /// Most columns the image may take (defaults to the terminal width)
+    #[arg(long = "image-width", value_name = "COLUMNS", requires = "image")]
+    pub image_width: Option<usize>,
+
+    /// Where the image goes relative to the greeting
+    #[arg(
+        long = "image-position",
+        value_enum,
+        value_name = "POSITION",
+        default_value_t = ImagePosition::Above,
+        requires = "image"
+    )]
+    pub image_position: ImagePosition,
+
+    /// Terminal graphics protocol for a sharp image, detected by default;
+    /// without one the image is drawn with characters
+    #[arg(
+        long = "image-protocol",
+        value_enum,
+        value_name = "PROTOCOL",
+        default_value_t = ImageProtocol::Auto,
+        requires = "image"
+    )]
+    pub image_protocol: ImageProtocol,
+
+    /// Write the greeting to a file instead of the terminal (.svg, .png, .html, .json, .ans, .txt)
+    #[arg(short = 'o', long = "output", value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Output format, overriding the --output extension; without --output
+    /// the result goes to stdout, even when it is not a terminal
+    #[arg(long = "format", value_enum)]
+    pub format: Option<ExportFormat>,
+
+    /// Animate the greeting
+    #[arg(long = "animate", value_enum, value_name = "STYLE")]
+    pub animate: Option<AnimationStyle>,
+
+    /// Milliseconds each animation frame is shown
+    #[arg(long = "speed", value_name = "MS", default_value_t = 100)]
+    pub speed: u64,
+
+    /// Record the animation to an asciinema v2 file instead of playing it
+    #[arg(
+        long = "record",
+        value_name = "PATH",
+        requires = "animate",
+        conflicts_with_all = ["output", "format"]
+    )]
+    pub record: Option<PathBuf>,
+
+    /// Print even if stdout is not a TTY (deprecated: use --format ansi or --format plain)
+    #[arg(long = "force")]
+    pub force: bool,
+}
+
+impl DisplayArgs {
+    fn font_options(&self) -> FontOptions {
+        FontOptions {
+            layout: self.layout,
+            pixel: self.pixel,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ShowArgs {
+    /// Greeting text, or - to read it from stdin
+    #[arg(required_unless_present = "file", conflicts_with = "file")]
+    pub text: Option<String>,
+
+    /// Read greeting text from a file
+    #[arg(long = "file", value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Maximum number of characters accepted from stdin or --file
+    #[arg(long = "max-input-length", value_name = "N")]
+    pub max_input_length: Option<usize>,
+
+    /// Save the text, font and size to config
     #[arg(long = "save")]
     pub save: bool,
 
+    #[command(flatten)]
+    pub display: DisplayArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReadArgs {
+    /// Rotation mode to use, overriding the config
+    #[arg(long = "rotation", value_enum)]
+    pub rotation: Option<RotationMode>,
+
+    #[command(flatten)]
+    pub display: DisplayArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MotdArgs {
+    /// Greeting text (defaults to the stored greeting)
+    pub text: Option<String>,
+
+    /// Write a getty issue banner (default target /etc/issue); backslashes in the art are escaped
+    #[arg(long = "issue")]
+    pub issue: bool,
+
+    /// Line appended verbatim below the art, e.g. '\n on \l' for /etc/issue (repeatable)
+    #[arg(long = "footer", value_name = "TEXT")]
+    pub footer: Vec<String>,
+
+    /// Print the banner to stdout, for use from an update-motd.d script
+    #[arg(long = "script", conflicts_with_all = ["dry_run", "output"])]
+    pub script: bool,
+
+    /// Show a diff against the current file instead of writing it
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub display: DisplayArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Display text as a themed greeting
+    Show(ShowArgs),
+
+    /// Display a stored greeting
+    Read(ReadArgs),
+
+    /// Write a login banner to /etc/motd, /etc/issue or stdout
+    Motd(MotdArgs),
+
+    /// Create default config, themes and fonts
+    Init {
+        /// Overwrite an existing config
+        #[arg(long = "force")]
+        force: bool,
+    },
+
+    /// Manage themes
+    #[command(subcommand)]
+    Theme(ThemeCommand),
+
+    /// Discover fonts
+    #[command(subcommand)]
+    Font(FontCommand),
+
+    /// Read and change config values
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// Store a named greeting for rotation with 'greetme read'
+    Add {
+        /// Unique name for the greeting
+        name: String,
+
+        /// Greeting text
+        text: String,
+
+        /// Theme to use for this greeting instead of the active theme
+        #[arg(long = "theme")]
+        theme: Option<String>,
+
+        /// Font to use for this greeting instead of the default font
+        #[arg(long = "font")]
+        font: Option<String>,
+
+        /// Relative weight for weighted rotation
+        #[arg(long = "weight", default_value = "1")]
+        weight: u32,
+    },
+
+    /// List stored greetings
+    List,
+
+    /// Remove a stored greeting
+    Remove {
+        /// Name of the greeting to remove
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ThemeCommand {
+    /// List available themes
+    List,
+
+    /// Set the active theme
+    Set {
+        /// Theme name
+        name: String,
+    },
+
     /// Preview a theme
-    #[arg(long = "preview-theme")]
-    pub preview_theme: Option<String>,
+    Preview {
+        /// Theme name
+        name: String,
 
-    /// Force operation even if not a TTY
-    #[arg(long = "force")]
-    pub force: bool,
+        /// Text to render
+        text: Option<String>,
+    },
 
-    /// Enable verbose output
-    #[arg(short = 'v', long = "verbose")]
-    pub verbose: bool,
+    /// Validate a theme by name or path
+    Check {
+        /// Theme name or path to a theme file
+        theme: String,
+    },
+
+    /// Copy a theme file into the themes directory
+    Import {
+        /// Path to a theme file
+        path: PathBuf,
+
+        /// Overwrite an existing theme with the same name
+        #[arg(long = "force")]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FontCommand {
+    /// List available fonts
+    List,
+
+    /// Render a sample in every available font, a page at a time
+    Preview {
+        /// Text to render (defaults to each font's name)
+        text: Option<String>,
+
+        /// Only preview this font
+        #[arg(long = "font")]
+        font: Option<String>,
+
+        /// How FIGlet characters are fitted together (defaults to each font's own layout)
+        #[arg(long = "layout", value_enum, value_name = "MODE")]
+        layout: Option<LayoutMode>,
+
+        /// How bitmap font pixels are drawn
+        #[arg(long = "pixel", value_enum, value_name = "MODE", default_value_t = PixelMode::Half)]
+        pixel: PixelMode,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print a config value, e.g. default_theme or info.layout
+    Get {
+        /// Config key
+        key: String,
+    },
+
+    /// Change a config value
+    Set {
+        /// Config key
+        key: String,
+
+        /// New value
+        value: String,
+    },
+
+    /// Open the config in $VISUAL or $EDITOR
+    Edit,
+
+    /// Print the config file path
+    Path,
 }
 
 pub fn execute(args: Args) -> Result<()> {
     let config_manager = ConfigManager::new()?;
+    let verbose = args.verbose;
 
-    // Handle create-config
-    if args.create_config {
-        return handle_create_config(&config_manager, args.force);
+    let command = match args.command {
+        Some(command) => command,
+        None => match legacy_command(args) {
+            Some(command) => command,
+            None => {
+                // No command specified
+                println!("No command specified. Use --help for usage information.");
+                return Ok(());
+            }
+        },
+    };
+
+    match command {
+        Command::Show(args) => handle_show(&config_manager, args),
+        Command::Read(args) => handle_read(&config_manager, args),
+        Command::Motd(args) => handle_motd(&config_manager, args),
+        Command::Init { force } => handle_create_config(&config_manager, force),
+        Command::Theme(command) => match command {
+            ThemeCommand::List => handle_list_themes(&config_manager),
+            ThemeCommand::Set { name } => handle_set_theme(&config_manager, &name),
+            ThemeCommand::Preview { name, text } => {
+                handle_preview_theme(&config_manager, &name, text.as_deref())
+            }
+            ThemeCommand::Check { theme } => handle_check_theme(&config_manager, &theme),
+            ThemeCommand::Import { path, force } => {
+                handle_import_theme(&config_manager, &path, force)
+            }
+        },
+        Command::Font(command) => match command {
+            FontCommand::List => handle_list_fonts(&config_manager),
+            FontCommand::Preview {
+                text,
+                font,
+                layout,
+                pixel,
+            } => handle_preview_fonts(
+                &config_manager,
+                text.as_deref(),
+                font.as_deref(),
+                &FontOptions { layout, pixel },
+            ),
+        },
+        Command::Config(command) => match command {
+            ConfigCommand::Get { key } => handle_config_get(&config_manager, &key),
+            ConfigCommand::Set { key, value } => handle_config_set(&config_manager, &key, &value),
+            ConfigCommand::Edit => handle_config_edit(&config_manager, verbose),
+            ConfigCommand::Path => {
+                println!("{}", config_manager.config_path().display());
+                Ok(())
+            }
+        },
+        Command::Add {
+            name,
+            text,
+            theme,
+            font,
+            weight,
+        } => handle_add(
+            &config_manager,
+            Greeting {
+                name,
+                text,
+                theme,
+                font,
+                weight,
+            },
+        ),
+        Command::List => handle_list(&config_manager),
+        Command::Remove { name } => handle_remove(&config_manager, &name),
     }
+}
 
-    // Handle list-themes
+/// Map the pre-subcommand flags onto their subcommand equivalents so existing
+/// scripts keep working
+fn legacy_command(args: Args) -> Option<Command> {
+    let deprecated = |flag: &str, replacement: &str| {
+        eprintln!("Warning: '{}' is deprecated, use '{}' instead.", flag, replacement);
+    };
+
+    if args.create_config {
+        deprecated("-c", "greetme init");
+        return Some(Command::Init {
+            force: args.display.force,
+        });
+    }
     if args.list_themes {
-        return handle_list_themes(&config_manager);
+        deprecated("--list-themes", "greetme theme list");
+        return Some(Command::Theme(ThemeCommand::List));
+    }
+    if let Some(name) = args.preview_theme {
+        deprecated("--preview-theme", "greetme theme preview");
+        return Some(Command::Theme(ThemeCommand::Preview { name, text: None }));
+    }
+    if let Some(name) = args.set_theme {
+        deprecated("--set-theme", "greetme theme set");
+        return Some(Command::Theme(ThemeCommand::Set { name }));
+    }
+    if args.text.is_some() || args.stdin.is_some() || args.file.is_some() {
+        match (&args.text, &args.file) {
+            (Some(_), _) => deprecated("-t", "greetme show"),
+            (None, Some(_)) => deprecated("--file", "greetme show --file"),
+            (None, None) => deprecated("-", "greetme show -"),
+        }
+        return Some(Command::Show(ShowArgs {
+            text: args.text.or(args.stdin),
+            file: args.file,
+            max_input_length: args.max_input_length,
+            save: args.save,
+            display: args.display,
+        }));
+    }
+    if args.read {
+        deprecated("-r", "greetme read");
+        return Some(Command::Read(ReadArgs {
+            rotation: args.rotation,
+            display: args.display,
+        }));
+    }
+    None
+}
+
+/// Read greeting text from a file, or from stdin when no path is given.
+/// Trailing blank lines are dropped so `echo` output renders cleanly.
+fn read_input(path: Option<&Path>, max_length: usize) -> Result<String> {
+    let reader: Box<dyn Read> = match path {
+        Some(path) => Box::new(
+            File::open(path).context(format!("Failed to open input file: {}", path.display()))?,
+        ),
+        None => Box::new(io::stdin()),
+    };
+
+    // A UTF-8 character is at most 4 bytes, so input that fills this bound
+    // has more than `max_length` characters, even if the read stopped
+    // partway through one
+    let limit = (max_length as u64).saturating_mul(4).saturating_add(4);
+    let mut bytes = Vec::new();
+    reader
+        .take(limit)
+        .read_to_end(&mut bytes)
+        .context("Failed to read greeting text")?;
+    let too_long = bytes.len() as u64 >= limit;
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(_) if too_long => String::new(),
+        Err(_) => return Err(anyhow!("Greeting text is not valid UTF-8")),
+    };
+
+    let content = content.trim_end();
+    if too_long || content.chars().count() > max_length {
+        return Err(anyhow!(
+            "Input is longer than {} characters. Use --max-input-length to raise the limit.",
+            max_length
+        ));
+    }
+    if content.trim().is_empty() {
+        return Err(anyhow!("No greeting text provided on input"));
     }
+    Ok(content.to_string())
+}
 
-    // Handle preview-theme
-    if let Some(theme_name) = args.preview_theme {
-        return handle_preview_theme(&config_manager, &theme_name);
+fn check_tty(display: &DisplayArgs) -> Result<()> {
+    // File, document and recorded output don't touch the terminal
+    if display.output.is_some() || display.format.is_some() || display.record.is_some() {
+        return Ok(());
     }
+    if !display.force && !io::stdout().is_terminal() {
+        return Err(anyhow!(
+            "Output is not a terminal. Use --format plain or --format ansi to write to a pipe, or -o FILE."
+        ));
+    }
+    Ok(())
+}
 
-    // Handle set-theme
-    if let Some(theme_name) = args.set_theme {
-        return handle_set_theme(&config_manager, &theme_name);
+/// Where the image goes in a pixel protocol, if the greeting is going
+/// somewhere that can show one: the terminal, or ANSI output when a
+/// protocol is named explicitly
+fn image_protocol(display: &DisplayArgs) -> Option<Protocol> {
+    if display.image.is_none() || display.animate.is_some() || display.record.is_some() {
+        return None;
     }
+    let format = match (display.format, &display.output) {
+        (Some(format), _) => Some(format),
+        (None, Some(path)) => ExportFormat::from_path(path).ok(),
+        (None, None) => return display.image_protocol.resolve(),
+    };
+    match display.image_protocol {
+        ImageProtocol::Auto => None,
+        protocol if format == Some(ExportFormat::Ansi) => protocol.resolve(),
+        _ => None,
+    }
+}
 
-    // Handle text display
-    if let Some(text) = args.text {
-        return handle_display_text(&config_manager, &text, args.font, args.size, args.save, args.force);
+/// Render the greeting with the optional image, info panel and quote. An
+/// image drawn with `protocol` comes back as an overlay to print after it.
+fn compose(
+    renderer: &Renderer,
+    config: &Config,
+    text: &str,
+    display: &DisplayArgs,
+    protocol: Option<Protocol>,
+) -> Result<(CellGrid, Option<Overlay>)> {
+    let quote = if display.quote {
+        Some(select_quote(
+            display.quote_file.clone(),
+            display.quote_max_length,
+            display.quote_match.as_deref(),
+        )?)
+    } else {
+        None
+    };
+
+    let mut grid = renderer.art_grid(text);
+    let mut overlay = None;
+    if let Some(path) = &display.image {
+        let image = picture::load(path)?;
+        let max_width = display.image_width.unwrap_or_else(terminal_width);
+        let cells = match protocol {
+            Some(protocol) => {
+                let (reserved, pixels) = graphics::draw(&image, protocol, max_width)?;
+                overlay = Some(pixels);
+                reserved
+            }
+            None => picture::to_grid(
+                &image,
+                &ImageOptions {
+                    mode: display.image_mode,
+                    dither: display.dither,
+                    max_width,
+                },
+            ),
+        };
+        renderer.add_image(&mut grid, cells, display.image_position);
+    }
+    if display.info || config.info.enabled {
+        let info = sysinfo::collect(&config.info.fields);
+        renderer.add_panel(&mut grid, &info, config.info.layout);
     }
+    if let Some(quote) = &quote {
+        renderer.add_subtitle(&mut grid, quote);
+    }
+    Ok((grid, overlay))
+}
 
-    // Handle read
-    if args.read {
-        return handle_read(&config_manager, args.size, args.force);
+fn show(renderer: &Renderer, config: &Config, text: &str, display: &DisplayArgs) -> Result<()> {
+    let (grid, overlay) = compose(renderer, config, text, display, image_protocol(display))?;
+    let overlay = overlay.map_or_else(String::new, |overlay| overlay.after(grid.height()));
+
+    // Without --animate this is the greeting alone, a single still frame
+    let animator = TextAnimator::new(AnimationConfig {
+        speed: display.speed,
+        style: display.animate.unwrap_or(AnimationStyle::None),
+    });
+    let frames = animator.frames(&grid);
+
+    if let Some(path) = &display.record {
+        return export::write(path, cast::render(&frames)?.as_bytes());
+    }
+    if display.animate.is_some() && display.output.is_none() && display.format.is_none() {
+        return animator.play(&frames).context("Failed to play animation");
     }
 
-    // No command specified
-    println!("No command specified. Use --help for usage information.");
+    let format = match (display.format, &display.output) {
+        (Some(format), _) => format,
+        (None, Some(path)) => ExportFormat::from_path(path)?,
+        (None, None) => {
+            renderer.print(&grid);
+            print!("{}", overlay);
+            return io::stdout().flush().context("Failed to write to stdout");
+        }
+    };
+
+    let document = Document {
+        grid: &grid,
+        frames: &frames,
+        theme: renderer.theme(),
+        font: renderer.font_name(),
+        size: renderer.size(),
+    };
+    let mut content = export::render(format, &document, &config.export)?;
+    content.extend(overlay.into_bytes());
+    match &display.output {
+        Some(path) => export::write(path, &content)?,
+        None => io::stdout()
+            .write_all(&content)
+            .context("Failed to write to stdout")?,
+    }
     Ok(())
 }
 
@@ -121,12 +735,12 @@ fn handle_create_config(config_manager: &ConfigManager, force: bool) -> Result<(
 fn handle_list_themes(config_manager: &ConfigManager) -> Result<()> {
     let config = config_manager
         .load_config()
-        .context("Failed to load config. Run 'greetme -c' to create default config.")?;
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
     let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
     let themes = theme_manager.list_themes()?;
 
     if themes.is_empty() {
-        println!("No themes found. Run 'greetme -c' to create default themes.");
+        println!("No themes found. Run 'greetme init' to create default themes.");
         return Ok(());
     }
 
@@ -141,31 +755,40 @@ fn handle_list_themes(config_manager: &ConfigManager) -> Result<()> {
     Ok(())
 }
 
-fn handle_preview_theme(config_manager: &ConfigManager, theme_name: &str) -> Result<()> {
+fn handle_preview_theme(
+    config_manager: &ConfigManager,
+    theme_name: &str,
+    text: Option<&str>,
+) -> Result<()> {
     let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
     let theme = theme_manager
         .load_theme(theme_name)
         .context(format!("Failed to load theme '{}'", theme_name))?;
 
     let config = config_manager.load_config().unwrap_or_default();
-    let renderer = Renderer::new(theme, &config.default_font, config.font_size);
+    let font = load_font(
+        config_manager,
+        None,
+        &config.default_font,
+        &config.font_fallback,
+        &FontOptions::default(),
+    )?;
+    let renderer = Renderer::new(theme, font, config.font_size);
 
     println!("Preview of theme '{}':", theme_name);
-    renderer.render("PREVIEW")?;
+    renderer.render(text.unwrap_or("PREVIEW"));
     Ok(())
 }
 
 fn handle_set_theme(config_manager: &ConfigManager, theme_name: &str) -> Result<()> {
     // Verify theme exists
     let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
-    theme_manager
-        .load_theme(theme_name)
-        .context(format!("Theme '{}' not found", theme_name))?;
+    theme_manager.load_theme(theme_name)?;
 
     // Load and update config
     let mut config = config_manager
         .load_config()
-        .context("Failed to load config. Run 'greetme -c' to create default config.")?;
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
     config.default_theme = theme_name.to_string();
     config_manager.save_config(&config)?;
 
@@ -173,45 +796,282 @@ fn handle_set_theme(config_manager: &ConfigManager, theme_name: &str) -> Result<
     Ok(())
 }
 
-fn handle_display_text(
+fn handle_check_theme(config_manager: &ConfigManager, theme: &str) -> Result<()> {
+    let path = Path::new(theme);
+    let loaded = if path.is_file() {
+        themes::load_theme_file(path)?
+    } else {
+        ThemeManager::new(config_manager.themes_dir())?.load_theme(theme)?
+    };
+    loaded.validate()?;
+
+    println!("✓ Theme '{}' is valid", loaded.name);
+    Ok(())
+}
+
+fn handle_import_theme(config_manager: &ConfigManager, path: &Path, force: bool) -> Result<()> {
+    let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
+    let name = theme_manager.import_theme(path, force)?;
+
+    println!("✓ Imported theme '{}'", name);
+    Ok(())
+}
+
+fn handle_list_fonts(config_manager: &ConfigManager) -> Result<()> {
+    let config = config_manager.load_config().unwrap_or_default();
+    let fonts = fonts::available(&config_manager.fonts_dir());
+
+    let name_width = fonts.iter().map(|font| width::width(&font.name)).max().unwrap_or(0);
+    let sources: Vec<String> = fonts.iter().map(|font| font.source.describe()).collect();
+    let source_width = sources.iter().map(|source| width::width(source)).max().unwrap_or(0);
+
+    println!("Available fonts:");
+    for (font, source) in fonts.iter().zip(&sources) {
+        let marker = if font.name == config.default_font { "*" } else { " " };
+        let (height, credit) = match font.backend(&FontOptions::default()) {
+            Ok(backend) => (
+                backend.metrics().height.to_string(),
+                font.credit().unwrap_or_default(),
+            ),
+            Err(e) => ("?".to_string(), format!("invalid font: {}", e)),
+        };
+        println!(
+            "  {} {}  {:>3}  {}  {}",
+            marker,
+            width::pad(&font.name, name_width),
+            height,
+            width::pad(source, source_width),
+            credit
+        );
+    }
+    Ok(())
+}
+
+fn handle_preview_fonts(
     config_manager: &ConfigManager,
-    text: &str,
-    font: Option<String>,
-    size: f32,
-    save: bool,
-    force: bool,
+    text: Option<&str>,
+    only: Option<&str>,
+    options: &FontOptions,
 ) -> Result<()> {
-    // Check if stdout is a TTY
-    if !force && !io::stdout().is_terminal() {
+    let config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+    let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
+    let theme = theme_manager.load_theme(&config.default_theme)?;
+
+    let fonts_dir = config_manager.fonts_dir();
+    let fonts = match only {
+        Some(name) => vec![fonts::find(name, &fonts_dir).ok_or_else(|| {
+            anyhow!(
+                "Font '{}' not found. Run 'greetme font list' to see available fonts.",
+                name
+            )
+        })?],
+        None => fonts::available(&fonts_dir),
+    };
+
+    // Page only when someone is at the terminal to turn pages
+    let page_height = match terminal_height() {
+        Some(height) if io::stdin().is_terminal() => Some(height.saturating_sub(1)),
+        _ => None,
+    };
+    let title_style = CellStyle::fg(theme.get_accent_color()).bold();
+    let error_style = CellStyle::fg(theme.get_dim_color());
+    let mut used = 0;
+
+    for font in fonts {
+        let mut block = CellGrid::new();
+        block.push_text(
+            &format!("{} ({})", font.name, font.source.describe()),
+            title_style,
+        );
+        let sample = text.unwrap_or(&font.name);
+        match font.backend(options) {
+            Ok(backend) => {
+                let mut missing: Vec<char> = Vec::new();
+                for ch in sample.chars() {
+                    if !ch.is_whitespace() && !backend.supports(ch) && !missing.contains(&ch) {
+                        missing.push(ch);
+                    }
+                }
+                if !missing.is_empty() {
+                    let missing: String = missing.into_iter().collect();
+                    block.push_text(&format!("  No glyphs for: {}", missing), error_style);
+                }
+                let renderer = Renderer::new(theme.clone(), backend, config.font_size);
+                block.append_below(renderer.art_grid(sample));
+            }
+            Err(e) => block.push_text(&format!("  {}", e), error_style),
+        }
+        block.push_text("", CellStyle::default());
+
+        if let Some(page_height) = page_height {
+            if used > 0 && used + block.height() > page_height {
+                if !next_page()? {
+                    return Ok(());
+                }
+                used = 0;
+            }
+        }
+        for line in block.to_ansi_lines() {
+            println!("{}", line);
+        }
+        used += block.height();
+    }
+    Ok(())
+}
+
+/// Wait for the reader to ask for the next page; false means stop
+fn next_page() -> Result<bool> {
+    print!("-- More -- (Enter for next page, q to quit) ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(!answer.trim().eq_ignore_ascii_case("q"))
+}
+
+/// Resolve the requested font, or else the configured one, from the user's
+/// font directory and the built-ins, and load it for rendering. A font named
+/// on the command line or given as a path must exist; a configured name that
+/// doesn't falls back to the default font. Characters it has no glyph for
+/// come from the fallback fonts that can be found.
+fn load_font(
+    config_manager: &ConfigManager,
+    requested: Option<&str>,
+    configured: &str,
+    fallback: &[String],
+    options: &FontOptions,
+) -> Result<Box<dyn FontBackend>> {
+    let fonts_dir = config_manager.fonts_dir();
+    let name = requested.unwrap_or(configured);
+    let primary = match fonts::find(name, &fonts_dir) {
+        Some(font) => font,
+        None if requested.is_some() || fonts::is_path(name) => {
+            return Err(anyhow!(
+                "Font '{}' not found. Run 'greetme font list' to see available fonts.",
+                name
+            ));
+        }
+        None => fonts::default_font(),
+    }
+    .backend(options)?;
+    // A broken fallback font only costs the characters it would have drawn
+    let fallbacks = fallback
+        .iter()
+        .filter_map(|name| match fonts::find(name, &fonts_dir).map(|font| font.backend(options)) {
+            Some(Ok(backend)) => Some(backend),
+            Some(Err(e)) => {
+                eprintln!("Warning: skipping fallback font '{}': {:#}", name, e);
+                None
+            }
+            None => {
+                eprintln!("Warning: skipping fallback font '{}': font not found", name);
+                None
+            }
+        })
+        .collect();
+    Ok(Box::new(FallbackBackend::new(primary, fallbacks)))
+}
+
+fn handle_config_get(config_manager: &ConfigManager, key: &str) -> Result<()> {
+    let config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    match config.get_value(key)? {
+        toml::Value::String(value) => println!("{}", value),
+        toml::Value::Table(table) => print!("{}", toml::to_string_pretty(&table)?),
+        value => println!("{}", value),
+    }
+    Ok(())
+}
+
+fn handle_config_set(config_manager: &ConfigManager, key: &str, value: &str) -> Result<()> {
+    let mut config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    config.set_value(key, value)?;
+    config_manager.save_config(&config)?;
+
+    println!("✓ Set {} to {}", key, value);
+    Ok(())
+}
+
+fn handle_config_edit(config_manager: &ConfigManager, verbose: bool) -> Result<()> {
+    if !config_manager.config_exists() {
         return Err(anyhow!(
-            "Output is not a terminal. Use --force to print anyway."
+            "No config at {}. Run 'greetme init' to create default config.",
+            config_manager.config_path().display()
         ));
     }
 
-    // Validate size
-    if size <= 0.0 || size > 10.0 {
-        return Err(anyhow!("Font size must be between 0.1 and 10.0"));
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    if verbose {
+        eprintln!("Opening {} with {}", config_manager.config_path().display(), editor);
     }
 
+    let status = std::process::Command::new(&editor)
+        .arg(config_manager.config_path())
+        .status()
+        .context(format!("Failed to launch editor '{}'", editor))?;
+    if !status.success() {
+        return Err(anyhow!("Editor '{}' exited with {}", editor, status));
+    }
+
+    // Report mistakes now rather than on the next greeting
+    config_manager
+        .load_config()
+        .context("Config is invalid after editing")?;
+    Ok(())
+}
+
+fn handle_show(config_manager: &ConfigManager, args: ShowArgs) -> Result<()> {
+    let display = &args.display;
+
     let mut config = config_manager
         .load_config()
-        .context("Failed to load config. Run 'greetme -c' to create default config.")?;
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    // Resolve greeting text from the argument, stdin or --file
+    let max_length = args.max_input_length.unwrap_or(config.max_input_length);
+    let text = match (args.text, &args.file) {
+        (Some(text), _) if text == "-" => read_input(None, max_length)?,
+        (Some(text), _) => text,
+        (None, Some(path)) => read_input(Some(path), max_length)?,
+        (None, None) => return Err(anyhow!("No greeting text given")),
+    };
+
+    check_tty(display)?;
+
+    // Validate size
+    if display.size <= 0.0 || display.size > 10.0 {
+        return Err(anyhow!("Font size must be between 0.1 and 10.0"));
+    }
 
     let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
     let theme = theme_manager.load_theme(&config.default_theme)?;
 
-    let font_name = font.as_ref().unwrap_or(&config.default_font);
-    let renderer = Renderer::new(theme, font_name, size);
+    let font = load_font(
+        config_manager,
+        display.font.as_deref(),
+        &config.default_font,
+        &config.font_fallback,
+        &display.font_options(),
+    )?;
+    let renderer = Renderer::new(theme, font, display.size);
 
-    renderer.render(text)?;
+    show(&renderer, &config, &text, display)?;
 
     // Save if requested
-    if save {
-        config.last_shown = Some(text.to_string());
+    if args.save {
+        config.last_shown = Some(text);
         config.last_updated = Some(chrono::Utc::now());
-        config.font_size = size;
-        if let Some(f) = font {
-            config.default_font = f;
+        config.font_size = display.size;
+        if let Some(f) = &display.font {
+            config.default_font = f.clone();
         }
         config_manager.save_config(&config)?;
     }
@@ -219,29 +1079,225 @@ fn handle_display_text(
     Ok(())
 }
 
-fn handle_read(config_manager: &ConfigManager, size: f32, force: bool) -> Result<()> {
-    // Check if stdout is a TTY
-    if !force && !io::stdout().is_terminal() {
-        return Err(anyhow!(
-            "Output is not a terminal. Use --force to print anyway."
-        ));
-    }
+fn handle_read(config_manager: &ConfigManager, args: ReadArgs) -> Result<()> {
+    let display = &args.display;
 
     let config = config_manager
         .load_config()
-        .context("Failed to load config. Run 'greetme -c' to create default config.")?;
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
 
-    let text = config.last_shown.as_ref().ok_or_else(|| {
-        anyhow!("No greeting stored in config. Use 'greetme -t \"Your text\" --save' to store one.")
-    })?;
+    check_tty(display)?;
+
+    let mode = args.rotation.unwrap_or(config.rotation);
+    let mut state = RotationState::load(&config_manager.state_path())?;
+    let picked = greetings::select(
+        &config.greetings,
+        mode,
+        &mut state,
+        chrono::Local::now().date_naive(),
+        &mut rand::thread_rng(),
+    );
+
+    let (text, theme_name, font_name) = match picked {
+        Some(index) => {
+            let greeting = &config.greetings[index];
+            if mode == RotationMode::Sequential {
+                state.save(&config_manager.state_path())?;
+            }
+            (
+                greeting.text.as_str(),
+                greeting.theme.as_ref().unwrap_or(&config.default_theme),
+                greeting.font.as_ref().unwrap_or(&config.default_font),
+            )
+        }
+        None => {
+            let text = config.last_shown.as_ref().ok_or_else(|| {
+                anyhow!("No greeting stored in config. Use 'greetme show \"Your text\" --save' to store one.")
+            })?;
+            (text.as_str(), &config.default_theme, &config.default_font)
+        }
+    };
 
     let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
-    let theme = theme_manager.load_theme(&config.default_theme)?;
-    
+    let theme = theme_manager.load_theme(theme_name)?;
+
     // Use provided size or config size
-    let font_size = if size != 1.0 { size } else { config.font_size };
-    let renderer = Renderer::new(theme, &config.default_font, font_size);
+    let font_size = if display.size != 1.0 { display.size } else { config.font_size };
+    let font = load_font(
+        config_manager,
+        display.font.as_deref(),
+        font_name,
+        &config.font_fallback,
+        &display.font_options(),
+    )?;
+    let renderer = Renderer::new(theme, font, font_size);
+
+    show(&renderer, &config, text, display)?;
+    Ok(())
+}
+
+fn handle_motd(config_manager: &ConfigManager, args: MotdArgs) -> Result<()> {
+    let display = &args.display;
+
+    let config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    // Banners are text files, plain unless escapes are asked for
+    let format = match display.format {
+        None | Some(ExportFormat::Plain) => ExportFormat::Plain,
+        Some(ExportFormat::Ansi) => ExportFormat::Ansi,
+        Some(_) => {
+            return Err(anyhow!(
+                "Login banners are text. Use --format plain or --format ansi."
+            ))
+        }
+    };
+
+    let target = match &display.output {
+        Some(path) => path.clone(),
+        None if args.issue => PathBuf::from(motd::DEFAULT_ISSUE_PATH),
+        None => PathBuf::from(motd::DEFAULT_MOTD_PATH),
+    };
+    let issue = args.issue || (!args.script && motd::is_issue_path(&target));
+
+    let text = args
+        .text
+        .clone()
+        .or_else(|| config.last_shown.clone())
+        .unwrap_or_else(|| config.default_text.clone());
+
+    let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
+    let theme = theme_manager.load_theme(&config.default_theme)?;
+    let font_size = if display.size != 1.0 { display.size } else { config.font_size };
+    let font = load_font(
+        config_manager,
+        display.font.as_deref(),
+        &config.default_font,
+        &config.font_fallback,
+        &display.font_options(),
+    )?;
+    let renderer = Renderer::new(theme, font, font_size);
+
+    // Banners are read back later, by whatever terminal logs in
+    let (grid, _) = compose(&renderer, &config, &text, display, None)?;
+    let document = Document {
+        grid: &grid,
+        frames: &[],
+        theme: renderer.theme(),
+        font: renderer.font_name(),
+        size: renderer.size(),
+    };
+    let rendered = String::from_utf8(export::render(format, &document, &config.export)?)
+        .context("Rendered banner is not valid UTF-8")?;
+    let content = motd::banner(&rendered, &args.footer, issue);
+
+    if args.script {
+        print!("{}", content);
+        return Ok(());
+    }
+
+    if args.dry_run {
+        let current = match fs::read_to_string(&target) {
+            Ok(current) => current,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).context(format!("Failed to read {}", target.display())),
+        };
+        if current == content {
+            println!("{} is up to date", target.display());
+        } else {
+            print!("{}", motd::diff(&current, &content, &target));
+        }
+        return Ok(());
+    }
+
+    export::write(&target, content.as_bytes())?;
+    println!("✓ Wrote banner to {}", target.display());
+    Ok(())
+}
+
+fn select_quote(
+    source: Option<PathBuf>,
+    max_length: Option<usize>,
+    pattern: Option<&str>,
+) -> Result<String> {
+    let source = source.or_else(quotes::system_source).ok_or_else(|| {
+        anyhow!("No fortune database found. Use --quote-file to pick quotes from a local file.")
+    })?;
+
+    let filter = QuoteFilter {
+        max_length,
+        pattern: pattern
+            .map(Regex::new)
+            .transpose()
+            .context("Invalid --quote-match pattern")?,
+    };
+
+    let all_quotes = quotes::load_quotes(&source)?;
+    quotes::pick(&all_quotes, &filter, &mut rand::thread_rng())
+}
+
+fn handle_add(config_manager: &ConfigManager, greeting: Greeting) -> Result<()> {
+    let mut config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    // Verify theme exists
+    if let Some(theme_name) = &greeting.theme {
+        let theme_manager = ThemeManager::new(config_manager.themes_dir())?;
+        theme_manager.load_theme(theme_name)?;
+    }
+
+    let name = greeting.name.clone();
+    greetings::add(&mut config.greetings, greeting)?;
+    config.last_updated = Some(chrono::Utc::now());
+    config_manager.save_config(&config)?;
+
+    println!("✓ Added greeting '{}'", name);
+    Ok(())
+}
+
+fn handle_list(config_manager: &ConfigManager) -> Result<()> {
+    let config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    if config.greetings.is_empty() {
+        println!("No greetings stored. Use 'greetme add <NAME> <TEXT>' to add one.");
+        return Ok(());
+    }
+
+    println!("Stored greetings (rotation: {:?}):", config.rotation);
+    for greeting in &config.greetings {
+        let mut details = Vec::new();
+        if let Some(theme) = &greeting.theme {
+            details.push(format!("theme={}", theme));
+        }
+        if let Some(font) = &greeting.font {
+            details.push(format!("font={}", font));
+        }
+        if greeting.weight != 1 {
+            details.push(format!("weight={}", greeting.weight));
+        }
+
+        if details.is_empty() {
+            println!("  {}: {}", greeting.name, greeting.text);
+        } else {
+            println!("  {}: {} [{}]", greeting.name, greeting.text, details.join(", "));
+        }
+    }
+    Ok(())
+}
+
+fn handle_remove(config_manager: &ConfigManager, name: &str) -> Result<()> {
+    let mut config = config_manager
+        .load_config()
+        .context("Failed to load config. Run 'greetme init' to create default config.")?;
+
+    greetings::remove(&mut config.greetings, name)?;
+    config.last_updated = Some(chrono::Utc::now());
+    config_manager.save_config(&config)?;
 
-    renderer.render(text)?;
+    println!("✓ Removed greeting '{}'", name);
     Ok(())
 }
@@ -1,25 +1,47 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::export::ExportConfig;
+use crate::fonts;
+use crate::greetings::{Greeting, RotationMode};
+use crate::sysinfo::InfoConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub version: String,
     pub default_text: String,
     pub default_theme: String,
     pub default_font: String,
+    /// Fonts tried in order for characters the selected font has no glyph for
+    #[serde(default)]
+    pub font_fallback: Vec<String>,
     #[serde(default = "default_font_size")]
     pub font_size: f32,
     pub last_shown: Option<String>,
     pub last_updated: Option<DateTime<Utc>>,
+    #[serde(default = "default_max_input_length")]
+    pub max_input_length: usize,
+    #[serde(default)]
+    pub rotation: RotationMode,
+    #[serde(default)]
+    pub info: InfoConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub greetings: Vec<Greeting>,
 }
 
 fn default_font_size() -> f32 {
     1.0
 }
 
+fn default_max_input_length() -> usize {
+    1024
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -27,13 +49,77 @@ impl Default for Config {
             default_text: "Hello, world!".to_string(),
             default_theme: "onedark".to_string(),
             default_font: "standard".to_string(),
+            font_fallback: Vec::new(),
             font_size: 1.0,
             last_shown: Some("Hello, world!".to_string()),
             last_updated: Some(Utc::now()),
+            max_input_length: default_max_input_length(),
+            rotation: RotationMode::default(),
+            info: InfoConfig::default(),
+            export: ExportConfig::default(),
+            greetings: Vec::new(),
         }
     }
 }
 
+impl Config {
+    /// Look up a value by dotted key, e.g. `default_theme` or `info.layout`
+    pub fn get_value(&self, key: &str) -> Result<toml::Value> {
+        let root = toml::Value::try_from(self).context("Failed to serialize config")?;
+        let mut value = &root;
+        for part in key.split('.') {
+            value = value
+                .get(part)
+                .ok_or_else(|| anyhow!("Unknown config key: {}", key))?;
+        }
+        Ok(value.clone())
+    }
+
+    /// Set a value by dotted key. The raw value is parsed as TOML when the
+    /// key holds a non-string value, so `font_size 2.0` and `info.enabled
+    /// true` keep their types.
+    pub fn set_value(&mut self, key: &str, raw: &str) -> Result<()> {
+        let mut root = toml::Value::try_from(&*self).context("Failed to serialize config")?;
+        let (parents, leaf) = match key.rsplit_once('.') {
+            Some((parents, leaf)) => (parents.split('.').collect(), leaf),
+            None => (Vec::new(), key),
+        };
+
+        let mut table = root
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("Config is not a table"))?;
+        for part in parents {
+            table = table
+                .get_mut(part)
+                .and_then(toml::Value::as_table_mut)
+                .ok_or_else(|| anyhow!("Unknown config key: {}", key))?;
+        }
+
+        let value = match table.get(leaf) {
+            Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+            _ => parse_toml_value(raw),
+        };
+        table.insert(leaf.to_string(), value);
+
+        let updated: Config = root
+            .try_into()
+            .context(format!("Invalid value for {}: {}", key, raw))?;
+
+        // Keys the config doesn't know are dropped on deserialization
+        updated.get_value(key)?;
+        *self = updated;
+        Ok(())
+    }
+}
+
+/// Parse a raw command-line value as a TOML literal, falling back to a string
+fn parse_toml_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
 pub struct ConfigManager {
     config_dir: PathBuf,
 }
@@ -64,6 +150,10 @@ impl ConfigManager {
         self.config_dir.join("fonts")
     }
 
+    pub fn state_path(&self) -> PathBuf {
+        self.config_dir.join("state.toml")
+    }
+
     pub fn config_exists(&self) -> bool {
         self.config_path().exists()
     }
@@ -127,18 +217,10 @@ impl ConfigManager {
     }
 
     fn create_default_fonts(&self) -> Result<()> {
-        let fonts = vec![
-            ("standard", "Standard FIGlet font"),
-            ("big", "Big FIGlet font"),
-            ("small", "Small FIGlet font"),
-            ("banner", "Banner FIGlet font"),
-            ("block", "Block FIGlet font"),
-        ];
-
-        for (name, description) in fonts {
-            let path = self.fonts_dir().join(format!("{}.txt", name));
-            fs::write(&path, description)
-                .context(format!("Failed to create font: {}", name))?;
+        for font in fonts::builtins() {
+            let path = self.fonts_dir().join(format!("{}.flf", font.name));
+            fs::write(&path, font.bytes())
+                .context(format!("Failed to create font: {}", font.name))?;
             set_file_perms(&path)?;
         }
 
@@ -209,4 +291,43 @@ mod tests {
             assert!(path.exists(), "Theme {} should exist", theme);
         }
     }
+
+    #[test]
+    fn test_config_without_greetings_parses() {
+        let toml = "
+version = \"1.0\"
+default_text = \"Hello\"
+default_theme = \"onedark\"
+default_font = \"standard\"
+";
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.greetings.is_empty());
+        assert_eq!(config.rotation, RotationMode::Sequential);
+    }
+
+    #[test]
+    fn test_get_and_set_values() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.get_value("default_theme").unwrap().as_str(),
+            Some("onedark")
+        );
+
+        config.set_value("font_size", "2.5").unwrap();
+        assert_eq!(config.font_size, 2.5);
+
+        config.set_value("info.enabled", "true").unwrap();
+        assert!(config.info.enabled);
+
+        config.set_value("font_fallback", "[\"unifont\", \"/usr/share/consolefonts/x.psf\"]").unwrap();
+        assert_eq!(config.font_fallback, vec!["unifont", "/usr/share/consolefonts/x.psf"]);
+
+        config.set_value("default_theme", "true").unwrap();
+        assert_eq!(config.default_theme, "true");
+
+        assert!(config.set_value("font_size", "big").is_err());
+        assert!(config.set_value("no_such_key", "1").is_err());
+        assert!(config.get_value("info.nope").is_err());
+    }
 }
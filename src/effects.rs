@@ -1,110 +1,355 @@
-use crossterm::style::Color;
+//! Fills that color the inked cells of the art, set per theme under
+//! `[fill]`: a pattern or an image texture.
 
-pub struct TextEffect {
-    pub fill: FillStyle,
-    pub shade: ShadeStyle,
-    pub scale: f32,
-}
+use anyhow::Result;
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::path::{Path, PathBuf};
 
-pub enum FillStyle {
-    None,
-    Gradient(Vec<Color>),
-    Rainbow,
-    Pattern(PatternType),
-}
+use crate::animation::mix;
+use crate::grid::{Cell, CellGrid, Rgb};
+use crate::picture;
+use crate::themes::{self, Theme};
+
+/// Texture pixels at least this opaque color the cell they land on
+const OPAQUE: u8 = 128;
+
+/// Columns from one wave crest to the next at scale 1
+const WAVE_LENGTH: f32 = 8.0;
 
-pub enum ShadeStyle {
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "style", rename_all = "lowercase")]
+pub enum FillStyle {
+    /// The theme's art color
+    #[default]
     None,
-    ThreeD,
-    Shadow,
-    Glow,
+    /// A repeating pattern in two or more colors
+    Pattern(PatternFill),
+    /// An image sampled across the art, so the lettering shows through it
+    Texture(Texture),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PatternType {
+    /// Dots of the second color on the first
     Dots,
+    /// Horizontal lines
     Lines,
+    /// Colors blended in diagonal sine waves
     Waves,
     Checkers,
+    /// Vertical stripes
+    Stripes,
+    /// Diagonal lines
+    Hatch,
+    /// Colors blended at random, the same on every run
+    Noise,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternFill {
+    pub pattern: PatternType,
+    /// Cells each square, line or dot of the pattern covers
+    #[serde(default = "default_scale")]
+    pub scale: usize,
+    /// Colors the pattern alternates between, defaulting to the theme's
+    /// strong and accent colors
+    #[serde(default)]
+    pub colors: Vec<String>,
+}
+
+fn default_scale() -> usize {
+    1
 }
 
-impl Default for TextEffect {
-    fn default() -> Self {
-        TextEffect {
-            fill: FillStyle::None,
-            shade: ShadeStyle::None,
-            scale: 1.0,
+/// How a texture covers the art
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureMode {
+    /// One copy of the image scaled to the whole art
+    #[default]
+    Stretch,
+    /// Copies of the image at one pixel per cell, repeated
+    Tile,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Texture {
+    /// Image file, relative to the theme file
+    pub image: PathBuf,
+    #[serde(default)]
+    pub mode: TextureMode,
+    /// Pixels read when the theme is loaded
+    #[serde(skip)]
+    pixels: Option<RgbaImage>,
+}
+
+impl FillStyle {
+    /// Read any image the fill needs, resolving its path against the
+    /// directory of the theme file
+    pub fn load(&mut self, dir: &Path) -> Result<()> {
+        if let FillStyle::Texture(texture) = self {
+            texture.pixels = Some(picture::load(&dir.join(&texture.image))?);
+        }
+        Ok(())
+    }
+
+    /// Recolor the inked cells of the art, leaving spacing and styles alone
+    pub fn apply(&self, grid: &CellGrid, theme: &Theme) -> CellGrid {
+        match self {
+            FillStyle::None => grid.clone(),
+            FillStyle::Pattern(pattern) => apply_pattern(grid, pattern, theme),
+            FillStyle::Texture(texture) => apply_texture(grid, texture),
         }
     }
 }
 
-pub struct EffectRenderer {
-    effect: TextEffect,
+/// Whether a cell shows ink rather than spacing
+fn inked(cell: &Cell) -> bool {
+    !cell.is_continuation() && !cell.symbol.trim().is_empty()
+}
+
+/// Give every inked cell the foreground `color` picks for its row and
+/// column, when it picks one
+fn recolor(grid: &CellGrid, color: impl Fn(usize, usize) -> Option<Rgb>) -> CellGrid {
+    grid.map(|y, x, cell| {
+        let mut filled = cell.clone();
+        if inked(cell) {
+            if let Some(color) = color(y, x) {
+                filled.style.fg = Some(color);
+            }
+        }
+        filled
+    })
 }
 
-impl EffectRenderer {
-    pub fn new(effect: TextEffect) -> Self {
-        EffectRenderer { effect }
+/// The color `amount` of the way along a list of colors, from 0.0 at the
+/// first to 1.0 at the last
+fn blend(colors: &[Rgb], amount: f32) -> Rgb {
+    let position = amount.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+    let from = position.floor() as usize;
+    let to = (from + 1).min(colors.len() - 1);
+    mix(colors[from], colors[to], position - from as f32)
+}
+
+/// A value from 0.0 to 1.0 that looks random but depends only on the cell
+fn noise(column: usize, row: usize) -> f32 {
+    let mut hash =
+        (column as u32).wrapping_mul(0x9e37_79b1) ^ (row as u32).wrapping_mul(0x85eb_ca77);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    (hash & 0xff) as f32 / 255.0
+}
+
+fn apply_pattern(grid: &CellGrid, fill: &PatternFill, theme: &Theme) -> CellGrid {
+    let colors: Vec<Rgb> = if fill.colors.is_empty() {
+        vec![theme.get_strong_color(), theme.get_accent_color()]
+    } else {
+        fill.colors
+            .iter()
+            .map(|hex| themes::hex_to_color(hex))
+            .collect()
+    };
+    let scale = fill.scale.max(1);
+    let band = |index: usize| colors[index % colors.len()];
+
+    recolor(grid, |y, x| {
+        let (column, row) = (x / scale, y / scale);
+        Some(match fill.pattern {
+            PatternType::Dots if column % 2 == 0 && row % 2 == 0 => band(1),
+            PatternType::Dots => band(0),
+            PatternType::Lines => band(row),
+            PatternType::Stripes => band(column),
+            PatternType::Checkers => band(column + row),
+            PatternType::Hatch => band((x + y) / scale),
+            PatternType::Waves => {
+                let phase = TAU * (x + y) as f32 / (WAVE_LENGTH * scale as f32);
+                blend(&colors, 0.5 + 0.5 * phase.sin())
+            }
+            PatternType::Noise => blend(&colors, noise(column, row)),
+        })
+    })
+}
+
+fn apply_texture(grid: &CellGrid, texture: &Texture) -> CellGrid {
+    let Some(pixels) = &texture.pixels else {
+        return grid.clone();
+    };
+    let (width, height) = match texture.mode {
+        TextureMode::Stretch => (grid.width(), grid.height()),
+        // Cells are about twice as tall as wide, so a tile keeps its shape
+        // with half as many rows as the image has pixels
+        TextureMode::Tile => (
+            pixels.width() as usize,
+            (pixels.height() as usize).div_ceil(2),
+        ),
+    };
+    if width == 0 || height == 0 {
+        return grid.clone();
     }
+    let sample = imageops::resize(pixels, width as u32, height as u32, FilterType::Triangle);
+    recolor(grid, |y, x| {
+        let pixel = sample.get_pixel((x % width) as u32, (y % height) as u32);
+        (pixel[3] >= OPAQUE).then(|| Rgb::new(pixel[0], pixel[1], pixel[2]))
+    })
+}
 
-    pub fn apply(&self, text: &str) -> String {
-        let mut result = text.to_string();
-        
-        // Apply scaling if needed
-        if self.effect.scale != 1.0 {
-            result = self.scale_text(&result);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::CellStyle;
+    use image::Rgba;
+
+    const RED: Rgb = Rgb { r: 255, g: 0, b: 0 };
+    const BLUE: Rgb = Rgb { r: 0, g: 0, b: 255 };
 
-        // Apply fill effect
-        result = match &self.effect.fill {
-            FillStyle::None => result,
-            FillStyle::Gradient(colors) => self.apply_gradient(&result, colors),
-            FillStyle::Rainbow => self.apply_rainbow(&result),
-            FillStyle::Pattern(pattern) => self.apply_pattern(&result, pattern),
-        };
-
-        // Apply shading effect
-        result = match &self.effect.shade {
-            ShadeStyle::None => result,
-            ShadeStyle::ThreeD => self.apply_3d(&result),
-            ShadeStyle::Shadow => self.apply_shadow(&result),
-            ShadeStyle::Glow => self.apply_glow(&result),
-        };
-
-        result
+    fn art() -> CellGrid {
+        CellGrid::from_lines(
+            &["#  #".to_string(), "####".to_string()],
+            CellStyle::fg(Rgb::WHITE),
+        )
     }
 
-    fn scale_text(&self, text: &str) -> String {
-        // Implementation for text scaling
-        text.to_string()
+    fn theme() -> Theme {
+        toml::from_str(include_str!("../themes/dracula.toml")).unwrap()
     }
 
-    fn apply_gradient(&self, text: &str, colors: &[Color]) -> String {
-        // Implementation for gradient fill
-        text.to_string()
+    /// The art with `fill` applied
+    fn apply(fill: FillStyle) -> CellGrid {
+        fill.apply(&art(), &theme())
     }
 
-    fn apply_rainbow(&self, text: &str) -> String {
-        // Implementation for rainbow fill
-        text.to_string()
+    fn pattern(pattern: PatternType, scale: usize, colors: &[&str]) -> FillStyle {
+        FillStyle::Pattern(PatternFill {
+            pattern,
+            scale,
+            colors: colors.iter().map(|color| color.to_string()).collect(),
+        })
     }
 
-    fn apply_pattern(&self, text: &str, pattern: &PatternType) -> String {
-        // Implementation for pattern fill
-        text.to_string()
+    fn colors(grid: &CellGrid) -> Vec<Vec<Option<Rgb>>> {
+        grid.rows()
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.style.fg).collect())
+            .collect()
     }
 
-    fn apply_3d(&self, text: &str) -> String {
-        // Implementation for 3D effect
-        text.to_string()
+    fn texture(pixels: RgbaImage, mode: TextureMode) -> FillStyle {
+        FillStyle::Texture(Texture {
+            image: PathBuf::new(),
+            mode,
+            pixels: Some(pixels),
+        })
     }
 
-    fn apply_shadow(&self, text: &str) -> String {
-        // Implementation for shadow effect
-        text.to_string()
+    #[test]
+    fn test_stretched_texture_fills_only_ink() {
+        // Left half red, right half blue, stretched over two rows
+        let pixels = RgbaImage::from_fn(4, 1, |x, _| {
+            if x < 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        });
+        let filled = apply(texture(pixels, TextureMode::Stretch));
+        let white = Some(Rgb::WHITE);
+        assert_eq!(
+            colors(&filled),
+            vec![
+                vec![Some(RED), white, white, Some(BLUE)],
+                vec![Some(RED), Some(RED), Some(BLUE), Some(BLUE)],
+            ]
+        );
+        assert!(filled.rows()[0][0].style.bold == art().rows()[0][0].style.bold);
     }
 
-    fn apply_glow(&self, text: &str) -> String {
-        // Implementation for glow effect
-        text.to_string()
+    #[test]
+    fn test_tiled_texture_repeats_and_skips_transparency() {
+        // One red pixel, then a transparent one, two pixel rows to a cell
+        let pixels = RgbaImage::from_fn(2, 2, |x, _| {
+            if x == 0 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let filled = apply(texture(pixels, TextureMode::Tile));
+        let white = Some(Rgb::WHITE);
+        assert_eq!(colors(&filled)[1], vec![Some(RED), white, Some(RED), white]);
+
+        let parsed: FillStyle =
+            toml::from_str("style = \"texture\"\nimage = \"marble.png\"\nmode = \"tile\"").unwrap();
+        assert!(matches!(
+            parsed,
+            FillStyle::Texture(Texture {
+                mode: TextureMode::Tile,
+                ..
+            })
+        ));
+        // Unknown fill styles are rejected
+        assert!(toml::from_str::<FillStyle>("style = \"rainbow\"").is_err());
+    }
+
+    #[test]
+    fn test_patterns_alternate_colors_by_cell() {
+        let (red, blue) = ("#ff0000", "#0000ff");
+        let row = |fill: FillStyle| colors(&apply(fill))[1].clone();
+        let (r, b) = (Some(RED), Some(BLUE));
+
+        assert_eq!(
+            row(pattern(PatternType::Checkers, 1, &[red, blue])),
+            vec![b, r, b, r]
+        );
+        assert_eq!(
+            row(pattern(PatternType::Stripes, 2, &[red, blue])),
+            vec![r, r, b, b]
+        );
+        assert_eq!(
+            row(pattern(PatternType::Hatch, 1, &[red, blue])),
+            vec![b, r, b, r]
+        );
+        assert_eq!(
+            row(pattern(PatternType::Lines, 1, &[red, blue])),
+            vec![b; 4]
+        );
+        // Spacing keeps the art color
+        let checkers = apply(pattern(PatternType::Checkers, 1, &[red, blue]));
+        assert_eq!(
+            colors(&checkers)[0],
+            vec![r, Some(Rgb::WHITE), Some(Rgb::WHITE), b]
+        );
+
+        // Without colors the theme's strong and accent colors are used
+        let dots = colors(&apply(pattern(PatternType::Dots, 1, &[])));
+        assert_eq!(dots[0][0], Some(theme().get_accent_color()));
+        assert_eq!(dots[1][0], Some(theme().get_strong_color()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_waves_and_noise_blend_between_colors() {
+        let waves = pattern(PatternType::Waves, 1, &["#000000", "#ffffff"]);
+        let row = &colors(&apply(waves))[0];
+        // Halfway up the first crest, then past it
+        assert_eq!(row[0], Some(Rgb::new(128, 128, 128)));
+        assert_eq!(row[3], Some(Rgb::new(218, 218, 218)));
+
+        let noise = pattern(PatternType::Noise, 1, &["#000000", "#ffffff"]);
+        let first = apply(noise.clone());
+        assert_eq!(colors(&first), colors(&apply(noise)));
+        let values: std::collections::HashSet<String> = colors(&first)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(Rgb::to_hex)
+            .collect();
+        assert!(values.len() > 2);
+
+        let parsed: FillStyle = toml::from_str("style = \"pattern\"\npattern = \"hatch\"").unwrap();
+        assert_eq!(parsed, pattern(PatternType::Hatch, 1, &[]));
+    }
+}
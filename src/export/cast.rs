@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::json;
+
+use super::text;
+use crate::animation::Frame;
+
+#[derive(Serialize)]
+struct Header {
+    version: u32,
+    width: usize,
+    height: usize,
+    timestamp: i64,
+    env: Env,
+}
+
+#[derive(Serialize)]
+struct Env {
+    #[serde(rename = "TERM")]
+    term: &'static str,
+}
+
+/// Encode animation frames as an asciinema v2 recording. Each frame redraws
+/// the greeting in place, timestamped by the delays of the frames before it.
+pub fn render(frames: &[Frame]) -> Result<String> {
+    let width = frames.iter().map(|f| f.grid.width()).max().unwrap_or(0);
+    let height = frames.iter().map(|f| f.grid.height()).max().unwrap_or(0);
+
+    // One spare row so the newline after the last line doesn't scroll
+    let header = Header {
+        version: 2,
+        width: width.max(1),
+        height: height + 1,
+        timestamp: chrono::Utc::now().timestamp(),
+        env: Env {
+            term: "xterm-256color",
+        },
+    };
+    let mut cast = serde_json::to_string(&header).context("Failed to encode asciicast header")?;
+    cast.push('\n');
+
+    let mut elapsed_micros: u128 = 0;
+    let mut previous_height = 0;
+    for frame in frames {
+        let mut output = String::new();
+        if previous_height > 0 {
+            output.push_str(&format!("\x1b[{}A", previous_height));
+        }
+        // Recordings are replayed raw, so lines need explicit carriage returns
+        for line in text::render_ansi(&frame.grid).lines() {
+            output.push_str("\r\x1b[2K");
+            output.push_str(line);
+            output.push_str("\r\n");
+        }
+        previous_height = frame.grid.height();
+
+        let time = elapsed_micros as f64 / 1_000_000.0;
+        let event = serde_json::to_string(&json!([time, "o", output]))
+            .context("Failed to encode asciicast event")?;
+        cast.push_str(&event);
+        cast.push('\n');
+
+        elapsed_micros += frame.delay.as_micros();
+    }
+    Ok(cast)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{CellGrid, CellStyle};
+    use std::time::Duration;
+
+    #[test]
+    fn test_cast_header_and_timestamps() {
+        let frame = |text: &str| Frame {
+            grid: CellGrid::from_lines(&[text, text], CellStyle::default()),
+            delay: Duration::from_millis(100),
+        };
+        let cast = render(&[frame("a"), frame("ab"), frame("abc")]).unwrap();
+        let lines: Vec<serde_json::Value> = cast
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert!(cast.starts_with(r#"{"version":2,"#));
+        assert_eq!(lines[0]["width"], 3);
+        assert_eq!(lines[0]["height"], 3);
+        assert_eq!(lines[1], json!([0.0, "o", "\r\x1b[2Ka\r\n\r\x1b[2Ka\r\n"]));
+        assert_eq!(lines[3][0], 0.2);
+        assert!(lines[2][2].as_str().unwrap().starts_with("\x1b[2A"));
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Context, Result};
+use gif::{Encoder, Frame as GifFrame, Repeat};
+use image::RgbImage;
+use std::collections::HashMap;
+
+use super::png::{self, PngOptions};
+use crate::animation::Frame;
+use crate::grid::Rgb;
+use crate::themes::Theme;
+
+/// Colors a GIF palette can hold
+const MAX_COLORS: usize = 256;
+
+/// Shortest frame delay browsers honour, in hundredths of a second
+const MIN_DELAY: u16 = 2;
+
+/// Rasterize animation frames into a looping GIF. Frames share one global
+/// palette seeded with the theme colors, then the most common other colors
+/// (rainbow and fade steps), so the theme always renders exactly.
+pub fn render(frames: &[Frame], theme: &Theme, options: &PngOptions) -> Result<Vec<u8>> {
+    let images: Vec<RgbImage> = frames
+        .iter()
+        .map(|frame| png::rasterize(&frame.grid, options))
+        .collect::<Result<_>>()?;
+    let (width, height) = images
+        .first()
+        .map(|image| image.dimensions())
+        .ok_or_else(|| anyhow!("Nothing to render"))?;
+    let too_large = || anyhow!("Greeting is too large for a GIF ({}x{} pixels)", width, height);
+    let gif_width = u16::try_from(width).map_err(|_| too_large())?;
+    let gif_height = u16::try_from(height).map_err(|_| too_large())?;
+
+    let palette = palette(theme, &images);
+    let flat_palette: Vec<u8> = palette.iter().flat_map(|c| [c.r, c.g, c.b]).collect();
+    let mut indices: HashMap<[u8; 3], u8> = palette
+        .iter()
+        .enumerate()
+        .map(|(index, c)| ([c.r, c.g, c.b], index as u8))
+        .collect();
+
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = Encoder::new(&mut bytes, gif_width, gif_height, &flat_palette)
+            .context("Failed to start GIF")?;
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .context("Failed to write GIF")?;
+
+        for (frame, image) in frames.iter().zip(&images) {
+            let pixels: Vec<u8> = image
+                .pixels()
+                .map(|p| {
+                    *indices
+                        .entry(p.0)
+                        .or_insert_with(|| nearest(&palette, p.0))
+                })
+                .collect();
+
+            let mut gif_frame = GifFrame::from_indexed_pixels(gif_width, gif_height, pixels, None);
+            let centis = frame.delay.as_millis() / 10;
+            gif_frame.delay = u16::try_from(centis).unwrap_or(u16::MAX).max(MIN_DELAY);
+            encoder
+                .write_frame(&gif_frame)
+                .context("Failed to write GIF frame")?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Theme colors first, then every other color by how often it's drawn
+fn palette(theme: &Theme, images: &[RgbImage]) -> Vec<Rgb> {
+    let mut palette: Vec<Rgb> = Vec::new();
+    for color in [
+        theme.get_background_color(),
+        theme.get_foreground_color(),
+        theme.get_accent_color(),
+        theme.get_strong_color(),
+        theme.get_dim_color(),
+    ] {
+        if !palette.contains(&color) {
+            palette.push(color);
+        }
+    }
+
+    let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+    for image in images {
+        for pixel in image.pixels() {
+            *counts.entry(pixel.0).or_default() += 1;
+        }
+    }
+    let mut others: Vec<([u8; 3], usize)> = counts
+        .into_iter()
+        .filter(|(rgb, _)| !palette.contains(&Rgb::new(rgb[0], rgb[1], rgb[2])))
+        .collect();
+    // Most used first, ties broken by color so output is deterministic
+    others.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    palette.extend(
+        others
+            .into_iter()
+            .map(|(rgb, _)| Rgb::new(rgb[0], rgb[1], rgb[2])),
+    );
+    palette.truncate(MAX_COLORS);
+    palette
+}
+
+/// Index of the palette color closest to `rgb`
+fn nearest(palette: &[Rgb], rgb: [u8; 3]) -> u8 {
+    let distance = |c: &Rgb| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(c.r, rgb[0]) + d(c.g, rgb[1]) + d(c.b, rgb[2])
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| distance(c))
+        .map(|(index, _)| index as u8)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{CellGrid, CellStyle};
+    use crate::themes::test_theme;
+    use std::time::Duration;
+
+    #[test]
+    fn test_gif_frames_and_delays() {
+        let frame = |color: Rgb| Frame {
+            grid: CellGrid::from_lines(&["#"], CellStyle::fg(color)),
+            delay: Duration::from_millis(120),
+        };
+        let frames = [frame(Rgb::new(1, 2, 3)), frame(Rgb::WHITE)];
+
+        let bytes = render(&frames, &test_theme(), &PngOptions::default()).unwrap();
+        assert!(bytes.starts_with(b"GIF89a"));
+
+        let mut decoder = gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, vec![12, 12]);
+    }
+
+    #[test]
+    fn test_palette_starts_with_theme_and_quantizes() {
+        let theme = test_theme();
+        let mut image = RgbImage::new(20, 20);
+        for (i, pixel) in image.pixels_mut().enumerate() {
+            pixel.0 = [i as u8, (i / 256) as u8, 7];
+        }
+
+        let palette = palette(&theme, &[image]);
+        assert_eq!(palette.len(), MAX_COLORS);
+        assert_eq!(palette[2], Rgb::new(255, 0, 0));
+        assert_eq!(nearest(&palette, [250, 250, 250]), 1);
+    }
+}
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::grid::{CellGrid, CellStyle, Rgb};
+use crate::themes::Theme;
+
+/// Settings for HTML output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlOptions {
+    /// Wrap the `<pre>` block in a complete HTML document
+    #[serde(default)]
+    pub standalone: bool,
+    /// Color runs with classes bound to `--greetme-*` CSS variables instead
+    /// of inline colors, so the host page can restyle them
+    #[serde(default)]
+    pub css_classes: bool,
+}
+
+/// Render a cell grid as a `<pre>` block with one `<span>` per run of
+/// identically styled cells
+pub fn render(grid: &CellGrid, theme: &Theme, options: &HtmlOptions) -> String {
+    let roles = theme_roles(theme);
+    let mut html = String::new();
+
+    if options.standalone {
+        let _ = writeln!(html, "<!DOCTYPE html>");
+        let _ = writeln!(html, "<html>");
+        let _ = writeln!(html, "<head>");
+        let _ = writeln!(html, r#"<meta charset="utf-8">"#);
+        let _ = writeln!(html, "<title>{}</title>", escape(&theme.name));
+        if options.css_classes {
+            html.push_str(&stylesheet(&roles));
+        }
+        let _ = writeln!(html, "</head>");
+        let _ = writeln!(
+            html,
+            r#"<body style="margin:0;background-color:{}">"#,
+            theme.get_background_color().to_hex()
+        );
+    } else if options.css_classes {
+        html.push_str(&stylesheet(&roles));
+    }
+
+    let pre_attrs = match grid.background {
+        Some(bg) => match role_of(&roles, bg) {
+            Some(role) if options.css_classes => format!(r#" class="greetme greetme-bg-{}""#, role),
+            _ => format!(r#" class="greetme" style="background-color:{}""#, bg.to_hex()),
+        },
+        None => r#" class="greetme""#.to_string(),
+    };
+    let _ = write!(html, "<pre{}>", pre_attrs);
+
+    for (index, row) in grid.rows().iter().enumerate() {
+        if index > 0 {
+            html.push('\n');
+        }
+        for (style, text) in CellGrid::runs(row) {
+            let attrs = if options.css_classes {
+                class_attrs(&style, &roles)
+            } else {
+                inline_attrs(&style)
+            };
+            if attrs.is_empty() {
+                html.push_str(&escape(&text));
+            } else {
+                let _ = write!(html, "<span{}>{}</span>", attrs, escape(&text));
+            }
+        }
+    }
+    html.push_str("</pre>\n");
+
+    if options.standalone {
+        let _ = writeln!(html, "</body>");
+        let _ = writeln!(html, "</html>");
+    }
+    html
+}
+
+/// Theme colors by the role name used in classes and CSS variables
+fn theme_roles(theme: &Theme) -> [(&'static str, Rgb); 5] {
+    [
+        ("foreground", theme.get_foreground_color()),
+        ("background", theme.get_background_color()),
+        ("accent", theme.get_accent_color()),
+        ("strong", theme.get_strong_color()),
+        ("dim", theme.get_dim_color()),
+    ]
+}
+
+fn role_of(roles: &[(&'static str, Rgb)], color: Rgb) -> Option<&'static str> {
+    roles
+        .iter()
+        .find(|(_, rgb)| *rgb == color)
+        .map(|(role, _)| *role)
+}
+
+/// Class rules for each theme role. The variables fall back to this theme's
+/// colors when the host page doesn't define them.
+fn stylesheet(roles: &[(&'static str, Rgb)]) -> String {
+    let mut css = String::from("<style>\n");
+    for (role, color) in roles {
+        let _ = writeln!(
+            css,
+            ".greetme-fg-{role} {{ color: var(--greetme-{role}, {hex}); }}",
+            role = role,
+            hex = color.to_hex()
+        );
+        let _ = writeln!(
+            css,
+            ".greetme-bg-{role} {{ background-color: var(--greetme-{role}, {hex}); }}",
+            role = role,
+            hex = color.to_hex()
+        );
+    }
+    css.push_str(".greetme-bold { font-weight: bold; }\n");
+    css.push_str(".greetme-italic { font-style: italic; }\n");
+    css.push_str("</style>\n");
+    css
+}
+
+fn inline_attrs(style: &CellStyle) -> String {
+    let mut css = String::new();
+    if let Some(fg) = style.fg {
+        let _ = write!(css, "color:{};", fg.to_hex());
+    }
+    if let Some(bg) = style.bg {
+        let _ = write!(css, "background-color:{};", bg.to_hex());
+    }
+    if style.bold {
+        css.push_str("font-weight:bold;");
+    }
+    if style.italic {
+        css.push_str("font-style:italic;");
+    }
+
+    if css.is_empty() {
+        String::new()
+    } else {
+        format!(r#" style="{}""#, css.trim_end_matches(';'))
+    }
+}
+
+/// Classes for colors that match a theme role; any other color stays inline
+fn class_attrs(style: &CellStyle, roles: &[(&'static str, Rgb)]) -> String {
+    let mut classes = Vec::new();
+    let mut inline = CellStyle::default();
+
+    if let Some(fg) = style.fg {
+        match role_of(roles, fg) {
+            Some(role) => classes.push(format!("greetme-fg-{}", role)),
+            None => inline.fg = Some(fg),
+        }
+    }
+    if let Some(bg) = style.bg {
+        match role_of(roles, bg) {
+            Some(role) => classes.push(format!("greetme-bg-{}", role)),
+            None => inline.bg = Some(bg),
+        }
+    }
+    if style.bold {
+        classes.push("greetme-bold".to_string());
+    }
+    if style.italic {
+        classes.push("greetme-italic".to_string());
+    }
+
+    let mut attrs = String::new();
+    if !classes.is_empty() {
+        let _ = write!(attrs, r#" class="{}""#, classes.join(" "));
+    }
+    attrs.push_str(&inline_attrs(&inline));
+    attrs
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::themes::test_theme;
+
+    #[test]
+    fn test_inline_spans_merge_runs() {
+        let accent = CellStyle::fg(Rgb::new(255, 0, 0)).bold();
+        let mut grid = CellGrid::from_lines(&["<ab"], accent);
+        grid.push_text("  ", CellStyle::default());
+
+        let html = render(&grid, &test_theme(), &HtmlOptions::default());
+        assert_eq!(
+            html,
+            "<pre class=\"greetme\"><span style=\"color:#ff0000;font-weight:bold\">&lt;ab</span>\n  </pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_css_class_mode_uses_theme_roles() {
+        let mut grid = CellGrid::from_lines(&["a"], CellStyle::fg(Rgb::new(0, 0, 255)));
+        grid.push_text("b", CellStyle::fg(Rgb::new(1, 2, 3)).italic());
+        grid.background = Some(Rgb::new(0, 0, 0));
+        let options = HtmlOptions {
+            css_classes: true,
+            ..HtmlOptions::default()
+        };
+
+        let html = render(&grid, &test_theme(), &options);
+        assert!(html.contains(".greetme-fg-dim { color: var(--greetme-dim, #0000ff); }"));
+        assert!(html.contains(r#"<pre class="greetme greetme-bg-background">"#));
+        assert!(html.contains(r#"<span class="greetme-fg-dim">a</span>"#));
+        assert!(html.contains(r#"<span class="greetme-italic" style="color:#010203">b</span>"#));
+    }
+
+    #[test]
+    fn test_standalone_document() {
+        let grid = CellGrid::from_lines(&["hi"], CellStyle::default());
+        let options = HtmlOptions {
+            standalone: true,
+            ..HtmlOptions::default()
+        };
+
+        let html = render(&grid, &test_theme(), &options);
+        assert!(html.starts_with("<!DOCTYPE html>\n<html>\n"));
+        assert!(html.contains("<title>test</title>"));
+        assert!(html.trim_end().ends_with("</body>\n</html>"));
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::Document;
+use crate::grid::{Cell, Rgb};
+
+/// Version of the JSON layout, bumped on incompatible changes
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    version: u32,
+    theme: &'a str,
+    font: &'a str,
+    size: f32,
+    width: usize,
+    height: usize,
+    background: Option<String>,
+    rows: Vec<Vec<JsonCell>>,
+}
+
+#[derive(Serialize)]
+struct JsonCell {
+    /// Grapheme cluster, empty in the second column of a wide one
+    char: String,
+    fg: Option<String>,
+    bg: Option<String>,
+    bold: bool,
+    italic: bool,
+}
+
+impl From<&Cell> for JsonCell {
+    fn from(cell: &Cell) -> Self {
+        Self {
+            char: cell.symbol.clone(),
+            fg: cell.style.fg.map(Rgb::to_hex),
+            bg: cell.style.bg.map(Rgb::to_hex),
+            bold: cell.style.bold,
+            italic: cell.style.italic,
+        }
+    }
+}
+
+/// Serialize the rendered grid as rows of cells with the settings it was
+/// rendered with, so other tools can re-render or diff greetings
+pub fn render(document: &Document) -> Result<String> {
+    let grid = document.grid;
+    let json = JsonDocument {
+        version: FORMAT_VERSION,
+        theme: &document.theme.name,
+        font: document.font,
+        size: document.size,
+        width: grid.width(),
+        height: grid.height(),
+        background: grid.background.map(Rgb::to_hex),
+        rows: grid
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(JsonCell::from).collect())
+            .collect(),
+    };
+
+    let mut output = serde_json::to_string(&json).context("Failed to serialize JSON")?;
+    output.push('\n');
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{CellGrid, CellStyle};
+    use crate::themes::test_theme;
+
+    #[test]
+    fn test_json_metadata_and_cells() {
+        let mut grid = CellGrid::from_lines(&["ab", "c"], CellStyle::fg(Rgb::new(255, 0, 0)).bold());
+        grid.background = Some(Rgb::new(0, 0, 0));
+        let theme = test_theme();
+        let document = Document {
+            grid: &grid,
+            frames: &[],
+            theme: &theme,
+            font: "small",
+            size: 2.0,
+        };
+
+        let value: serde_json::Value = serde_json::from_str(&render(&document).unwrap()).unwrap();
+        assert_eq!(value["theme"], "test");
+        assert_eq!(value["font"], "small");
+        assert_eq!(value["size"], 2.0);
+        assert_eq!(value["width"], 2);
+        assert_eq!(value["height"], 2);
+        assert_eq!(value["background"], "#000000");
+        assert_eq!(
+            value["rows"][0][1],
+            serde_json::json!({"char": "b", "fg": "#ff0000", "bg": null, "bold": true, "italic": false})
+        );
+        assert_eq!(value["rows"][1].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_json_is_a_single_line() {
+        let grid = CellGrid::from_lines(&["\"\\"], CellStyle::default());
+        let theme = test_theme();
+        let document = Document {
+            grid: &grid,
+            frames: &[],
+            theme: &theme,
+            font: "standard",
+            size: 1.0,
+        };
+
+        let output = render(&document).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains(r#""char":"\"""#));
+    }
+}
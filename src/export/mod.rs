@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+use crate::animation::Frame;
+use crate::grid::CellGrid;
+use crate::themes::Theme;
+
+pub mod cast;
+mod cell_font;
+pub mod gif;
+pub mod html;
+pub mod json;
+pub mod png;
+pub mod svg;
+pub mod text;
+
+/// Settings for file exports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportConfig {
+    #[serde(default)]
+    pub svg: svg::SvgOptions,
+    #[serde(default)]
+    pub png: png::PngOptions,
+    #[serde(default)]
+    pub html: html::HtmlOptions,
+}
+
+/// Formats a rendered greeting can be written as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Svg,
+    Png,
+    /// Animated when --animate is given, otherwise a single frame
+    Gif,
+    Html,
+    Json,
+    /// Text with 24-bit color escapes, even when not writing to a terminal
+    Ansi,
+    /// Text without any styling
+    Plain,
+}
+
+impl ExportFormat {
+    /// Pick a format from the output file's extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("svg") => Ok(ExportFormat::Svg),
+            Some("png") => Ok(ExportFormat::Png),
+            Some("gif") => Ok(ExportFormat::Gif),
+            Some("html") | Some("htm") => Ok(ExportFormat::Html),
+            Some("json") => Ok(ExportFormat::Json),
+            Some("ans") => Ok(ExportFormat::Ansi),
+            Some("txt") => Ok(ExportFormat::Plain),
+            _ => Err(anyhow!(
+                "Unsupported output format for {}. Supported extensions: .svg, .png, .gif, .html, .json, .ans, .txt (or pass --format)",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A composed greeting and the settings it was rendered with
+pub struct Document<'a> {
+    pub grid: &'a CellGrid,
+    /// Animation frames ending on `grid`; empty for a still greeting
+    pub frames: &'a [Frame],
+    pub theme: &'a Theme,
+    pub font: &'a str,
+    pub size: f32,
+}
+
+/// Encode a rendered greeting in the given format
+pub fn render(format: ExportFormat, document: &Document, config: &ExportConfig) -> Result<Vec<u8>> {
+    let grid = document.grid;
+    let content = match format {
+        ExportFormat::Svg => svg::render(grid, &config.svg).into_bytes(),
+        ExportFormat::Png => png::render(grid, &config.png)?,
+        ExportFormat::Gif if document.frames.is_empty() => {
+            let still = Frame {
+                grid: grid.clone(),
+                delay: Duration::ZERO,
+            };
+            gif::render(&[still], document.theme, &config.png)?
+        }
+        ExportFormat::Gif => gif::render(document.frames, document.theme, &config.png)?,
+        ExportFormat::Html => html::render(grid, document.theme, &config.html).into_bytes(),
+        ExportFormat::Json => json::render(document)?.into_bytes(),
+        ExportFormat::Ansi => text::render_ansi(grid).into_bytes(),
+        ExportFormat::Plain => text::render_plain(grid).into_bytes(),
+    };
+    Ok(content)
+}
+
+/// Write encoded output to `path` atomically: the content goes to a
+/// temporary file beside it which then replaces `path`, so readers never see
+/// a partly written banner. An existing file keeps its permissions and, as
+/// far as the user may set it, its owner, and a symlink such as `/etc/motd`
+/// keeps pointing at the file it names.
+pub fn write(path: &Path, content: &[u8]) -> Result<()> {
+    let resolved = fs::canonicalize(path).ok();
+    let path = resolved.as_deref().unwrap_or(path);
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid output path: {}", path.display()))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        process::id()
+    ));
+
+    let result = write_synced(&temp_path, content)
+        .and_then(|()| keep_metadata(&temp_path, path))
+        .and_then(|()| {
+            fs::rename(&temp_path, path).context(format!("Failed to write {}", path.display()))
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Give the temporary file the permissions and owner of the file it
+/// replaces, if there is one
+fn keep_metadata(temp_path: &Path, path: &Path) -> Result<()> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    fs::set_permissions(temp_path, metadata.permissions())
+        .context(format!("Failed to set permissions on {}", temp_path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // Only root may hand a file to another user, so this is best effort
+        let _ = std::os::unix::fs::chown(temp_path, Some(metadata.uid()), Some(metadata.gid()));
+    }
+    Ok(())
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> Result<()> {
+    let mut file = File::create(path).context(format!("Failed to create {}", path.display()))?;
+    file.write_all(content)
+        .and_then(|()| file.sync_all())
+        .context(format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(ExportFormat::from_path(Path::new("a.HTM")).unwrap(), ExportFormat::Html);
+        assert_eq!(ExportFormat::from_path(Path::new("motd.txt")).unwrap(), ExportFormat::Plain);
+        assert!(ExportFormat::from_path(Path::new("banner")).is_err());
+    }
+
+    #[test]
+    fn test_write_replaces_file_without_leftovers() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("banner.txt");
+        fs::write(&path, "old contents that are longer").unwrap();
+
+        write(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_through_symlink_keeps_link_and_mode() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("motd.dynamic");
+        fs::write(&target, "old").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+        let link = temp_dir.path().join("motd");
+        symlink(&target, &link).unwrap();
+
+        write(&link, b"new").unwrap();
+
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_link(&link).unwrap(), target);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o640);
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+}
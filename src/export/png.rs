@@ -0,0 +1,399 @@
+use anyhow::{anyhow, Context, Result};
+use image::{ImageOutputFormat, Rgb as Pixel, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+use super::cell_font::{self, HEIGHT, WIDTH};
+use crate::grid::{CellGrid, CellStyle, Rgb};
+
+/// Rows of one rasterized cell, most significant bit leftmost
+type Glyph = [u8; HEIGHT as usize];
+
+/// Canvas color when the grid has no theme background
+const DEFAULT_BACKGROUND: Rgb = Rgb { r: 0, g: 0, b: 0 };
+
+/// Longest side of a rendered image, in pixels
+const MAX_SIDE: u32 = 32_768;
+
+/// Raster settings for PNG output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PngOptions {
+    /// Integer upscaling factor applied to every pixel
+    #[serde(default = "default_scale")]
+    pub scale: u32,
+    /// Border around the cells, in unscaled pixels
+    #[serde(default = "default_padding")]
+    pub padding: u32,
+}
+
+fn default_scale() -> u32 {
+    2
+}
+
+fn default_padding() -> u32 {
+    8
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            scale: default_scale(),
+            padding: default_padding(),
+        }
+    }
+}
+
+/// Rasterize a cell grid with the built-in 8x13 cell font and encode it as
+/// PNG. Block elements and box-drawing characters are drawn geometrically so
+/// they tile seamlessly across cells.
+pub fn render(grid: &CellGrid, options: &PngOptions) -> Result<Vec<u8>> {
+    let image = rasterize(grid, options)?;
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, ImageOutputFormat::Png)
+        .context("Failed to encode PNG")?;
+    Ok(bytes.into_inner())
+}
+
+/// Draw a cell grid onto an image with the built-in cell font
+pub fn rasterize(grid: &CellGrid, options: &PngOptions) -> Result<RgbImage> {
+    let scale = options.scale.max(1);
+    let padding = options.padding;
+    let width = side(grid.width(), WIDTH, options)?;
+    let height = side(grid.height(), HEIGHT, options)?;
+    let background = grid.background.unwrap_or(DEFAULT_BACKGROUND);
+    let mut image = RgbImage::from_pixel(width.max(1), height.max(1), pixel(background));
+
+    for (row_index, row) in grid.rows().iter().enumerate() {
+        for (column, cell) in row.iter().enumerate() {
+            let left = padding + column as u32 * WIDTH;
+            let top = padding + row_index as u32 * HEIGHT;
+
+            if let Some(bg) = cell.style.bg {
+                fill(&mut image, left * scale, top * scale, WIDTH * scale, HEIGHT * scale, bg);
+            }
+            if cell.is_blank() || cell.is_continuation() {
+                continue;
+            }
+
+            let fg = cell.style.fg.unwrap_or(Rgb::WHITE);
+            let glyph = glyph_for(cell.base(), &cell.style);
+            for (y, bits) in glyph.iter().enumerate() {
+                for x in 0..WIDTH {
+                    if bits & (0x80 >> x) != 0 {
+                        fill(
+                            &mut image,
+                            (left + x) * scale,
+                            (top + y as u32) * scale,
+                            scale,
+                            scale,
+                            fg,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(image)
+}
+
+/// Pixels along one side of the image for `cells` cells of `cell_size`
+/// pixels, with padding and scale from the config
+fn side(cells: usize, cell_size: u32, options: &PngOptions) -> Result<u32> {
+    u32::try_from(cells)
+        .ok()
+        .and_then(|cells| cells.checked_mul(cell_size))
+        .and_then(|pixels| pixels.checked_add(options.padding.checked_mul(2)?))
+        .and_then(|pixels| pixels.checked_mul(options.scale.max(1)))
+        .filter(|&pixels| pixels <= MAX_SIDE)
+        .ok_or_else(|| {
+            anyhow!(
+                "Image would be more than {} pixels across. Lower export.png.scale or export.png.padding.",
+                MAX_SIDE
+            )
+        })
+}
+
+fn pixel(color: Rgb) -> Pixel<u8> {
+    Pixel([color.r, color.g, color.b])
+}
+
+fn fill(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb) {
+    for py in y..y + height {
+        for px in x..x + width {
+            image.put_pixel(px, py, pixel(color));
+        }
+    }
+}
+
+/// Bitmap for a character: block and box glyphs first, then the ASCII font
+/// with bold and italic emulated, and an empty box for anything else
+fn glyph_for(ch: char, style: &CellStyle) -> Glyph {
+    if let Some(glyph) = block_glyph(ch).or_else(|| box_glyph(ch)) {
+        return glyph;
+    }
+
+    let Some(base) = cell_font::glyph(ch) else {
+        return missing_glyph();
+    };
+    let mut glyph = *base;
+    if style.bold {
+        // Overstrike one pixel to the right, like X11 does for fixed fonts
+        for row in glyph.iter_mut() {
+            *row |= *row >> 1;
+        }
+    }
+    if style.italic {
+        // Shear the top of the glyph rightwards
+        for (y, row) in glyph.iter_mut().enumerate() {
+            *row >>= (HEIGHT as usize - 1 - y) / 5;
+        }
+    }
+    glyph
+}
+
+fn missing_glyph() -> Glyph {
+    let mut glyph = [0x42; HEIGHT as usize];
+    glyph[0] = 0;
+    glyph[1] = 0x7e;
+    glyph[HEIGHT as usize - 2] = 0x7e;
+    glyph[HEIGHT as usize - 1] = 0;
+    glyph
+}
+
+/// Fill the rows `from..to` with `bits`
+fn rows(from: usize, to: usize, bits: u8) -> Glyph {
+    let mut glyph = [0; HEIGHT as usize];
+    for row in glyph.iter_mut().take(to).skip(from) {
+        *row = bits;
+    }
+    glyph
+}
+
+/// Number of rows covering `eighths`/8 of the cell height
+fn eighths(eighths: u32) -> usize {
+    ((HEIGHT * eighths + 4) / 8) as usize
+}
+
+/// Block elements (U+2580..U+259F)
+fn block_glyph(ch: char) -> Option<Glyph> {
+    const H: usize = HEIGHT as usize;
+    const MID: usize = H / 2;
+
+    let code = ch as u32;
+    let glyph = match code {
+        0x2580 => rows(0, MID, 0xff),
+        // Lower one eighth to seven eighths
+        0x2581..=0x2587 => rows(H - eighths(code - 0x2580), H, 0xff),
+        0x2588 => rows(0, H, 0xff),
+        // Left seven eighths to one eighth
+        0x2589..=0x258f => rows(0, H, 0xff << (code - 0x2588)),
+        0x2590 => rows(0, H, 0x0f),
+        0x2591..=0x2593 => {
+            let (even, odd) = match code {
+                0x2591 => (0x88, 0x22),
+                0x2592 => (0xaa, 0x55),
+                _ => (0x77, 0xdd),
+            };
+            let mut glyph = [0; H];
+            for (y, row) in glyph.iter_mut().enumerate() {
+                *row = if y % 2 == 0 { even } else { odd };
+            }
+            glyph
+        }
+        0x2594 => rows(0, eighths(1), 0xff),
+        0x2595 => rows(0, H, 0x01),
+        0x2596..=0x259f => {
+            // Quadrants as upper-left, upper-right, lower-left, lower-right
+            let quadrants: u8 = match code {
+                0x2596 => 0b0010,
+                0x2597 => 0b0001,
+                0x2598 => 0b1000,
+                0x2599 => 0b1011,
+                0x259a => 0b1001,
+                0x259b => 0b1110,
+                0x259c => 0b1101,
+                0x259d => 0b0100,
+                0x259e => 0b0110,
+                _ => 0b0111,
+            };
+            let half = |left: bool, right: bool| {
+                (if left { 0xf0 } else { 0 }) | (if right { 0x0f } else { 0 })
+            };
+            let mut glyph = rows(0, MID, half(quadrants & 0b1000 != 0, quadrants & 0b0100 != 0));
+            let lower = half(quadrants & 0b0010 != 0, quadrants & 0b0001 != 0);
+            for row in glyph.iter_mut().skip(MID) {
+                *row = lower;
+            }
+            glyph
+        }
+        _ => return None,
+    };
+    Some(glyph)
+}
+
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+/// Line weights of box-drawing characters as up, down, left, right
+fn box_arms(ch: char) -> Option<[u8; 4]> {
+    const L: u8 = LIGHT;
+    const H: u8 = HEAVY;
+    const D: u8 = DOUBLE;
+
+    let arms = match ch {
+        '─' | '┄' | '┈' | '╌' => [0, 0, L, L],
+        '━' | '┅' | '┉' | '╍' => [0, 0, H, H],
+        '│' | '┆' | '┊' | '╎' => [L, L, 0, 0],
+        '┃' | '┇' | '┋' | '╏' => [H, H, 0, 0],
+        '┌' | '╭' => [0, L, 0, L],
+        '┐' | '╮' => [0, L, L, 0],
+        '└' | '╰' => [L, 0, 0, L],
+        '┘' | '╯' => [L, 0, L, 0],
+        '├' => [L, L, 0, L],
+        '┤' => [L, L, L, 0],
+        '┬' => [0, L, L, L],
+        '┴' => [L, 0, L, L],
+        '┼' => [L, L, L, L],
+        '┏' => [0, H, 0, H],
+        '┓' => [0, H, H, 0],
+        '┗' => [H, 0, 0, H],
+        '┛' => [H, 0, H, 0],
+        '┣' => [H, H, 0, H],
+        '┫' => [H, H, H, 0],
+        '┳' => [0, H, H, H],
+        '┻' => [H, 0, H, H],
+        '╋' => [H, H, H, H],
+        '═' => [0, 0, D, D],
+        '║' => [D, D, 0, 0],
+        '╔' => [0, D, 0, D],
+        '╗' => [0, D, D, 0],
+        '╚' => [D, 0, 0, D],
+        '╝' => [D, 0, D, 0],
+        '╠' => [D, D, 0, D],
+        '╣' => [D, D, D, 0],
+        '╦' => [0, D, D, D],
+        '╩' => [D, 0, D, D],
+        '╬' => [D, D, D, D],
+        '╴' => [0, 0, L, 0],
+        '╵' => [L, 0, 0, 0],
+        '╶' => [0, 0, 0, L],
+        '╷' => [0, L, 0, 0],
+        _ => return None,
+    };
+    Some(arms)
+}
+
+/// Box-drawing characters, drawn as arms meeting in the middle of the cell
+fn box_glyph(ch: char) -> Option<Glyph> {
+    let [up, down, left, right] = box_arms(ch)?;
+    let vertical = up.max(down);
+    let horizontal = left.max(right);
+
+    // Columns of a vertical line and rows of a horizontal line by weight
+    let columns = |weight: u8| match weight {
+        LIGHT => 0x10,
+        HEAVY => 0x18,
+        DOUBLE => 0x24,
+        _ => 0,
+    };
+    let line_rows: fn(u8) -> &'static [usize] = |weight| match weight {
+        LIGHT => &[6],
+        HEAVY => &[6, 7],
+        DOUBLE => &[5, 7],
+        _ => &[],
+    };
+
+    // Arms reach across the perpendicular line so corners join up
+    let (top_reach, bottom_reach) = match horizontal {
+        0 => (6, 6),
+        DOUBLE => (7, 5),
+        HEAVY => (7, 6),
+        _ => (6, 6),
+    };
+    let (left_reach, right_reach) = match vertical {
+        0 => (3, 3),
+        DOUBLE => (5, 2),
+        HEAVY => (4, 3),
+        _ => (3, 3),
+    };
+
+    let mut glyph = [0; HEIGHT as usize];
+    if up != 0 {
+        for row in glyph.iter_mut().take(top_reach + 1) {
+            *row |= columns(up);
+        }
+    }
+    if down != 0 {
+        for row in glyph.iter_mut().skip(bottom_reach) {
+            *row |= columns(down);
+        }
+    }
+    if left != 0 {
+        let bits = 0xffu8 << (7 - left_reach);
+        for &y in line_rows(left) {
+            glyph[y] |= bits;
+        }
+    }
+    if right != 0 {
+        let bits = 0xffu8 >> right_reach;
+        for &y in line_rows(right) {
+            glyph[y] |= bits;
+        }
+    }
+    Some(glyph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::CellStyle;
+
+    #[test]
+    fn test_png_dimensions_include_scale_and_padding() {
+        let grid = CellGrid::from_lines(&["ab", "abc"], CellStyle::default());
+        let options = PngOptions {
+            scale: 2,
+            padding: 4,
+        };
+
+        let image = rasterize(&grid, &options).unwrap();
+        assert_eq!(image.width(), (3 * WIDTH + 8) * 2);
+        assert_eq!(image.height(), (2 * HEIGHT + 8) * 2);
+
+        let bytes = render(&grid, &options).unwrap();
+        assert!(bytes.starts_with(b"\x89PNG"));
+
+        // Huge scale or padding from the config is an error, not an overflow
+        for (scale, padding) in [(u32::MAX, 4), (2, u32::MAX), (5000, 0)] {
+            let error = render(&grid, &PngOptions { scale, padding }).unwrap_err();
+            assert!(error.to_string().contains("Lower export.png.scale"));
+        }
+    }
+
+    #[test]
+    fn test_full_block_fills_cell_with_foreground() {
+        let red = Rgb::new(255, 0, 0);
+        let mut grid = CellGrid::from_lines(&["█ "], CellStyle::fg(red));
+        grid.background = Some(Rgb::new(0, 0, 255));
+        let options = PngOptions {
+            scale: 1,
+            padding: 0,
+        };
+
+        let image = rasterize(&grid, &options).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Pixel([255, 0, 0]));
+        assert_eq!(*image.get_pixel(WIDTH - 1, HEIGHT - 1), Pixel([255, 0, 0]));
+        assert_eq!(*image.get_pixel(WIDTH, 0), Pixel([0, 0, 255]));
+    }
+
+    #[test]
+    fn test_box_corners_join() {
+        let corner = box_glyph('┌').unwrap();
+        assert_eq!(corner[6], 0x1f);
+        assert_eq!(corner[5], 0);
+        assert_eq!(corner[12], 0x10);
+        assert!(box_glyph('a').is_none());
+    }
+}
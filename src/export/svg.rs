@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::grid::CellGrid;
+use crate::width;
+
+/// Font and cell metrics for SVG output, in pixels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SvgOptions {
+    #[serde(default = "default_font_family")]
+    pub font_family: String,
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_cell_width")]
+    pub cell_width: f32,
+    #[serde(default = "default_cell_height")]
+    pub cell_height: f32,
+}
+
+fn default_font_family() -> String {
+    "monospace".to_string()
+}
+
+fn default_font_size() -> f32 {
+    14.0
+}
+
+fn default_cell_width() -> f32 {
+    8.4
+}
+
+fn default_cell_height() -> f32 {
+    17.0
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            font_family: default_font_family(),
+            font_size: default_font_size(),
+            cell_width: default_cell_width(),
+            cell_height: default_cell_height(),
+        }
+    }
+}
+
+/// Render a cell grid as an SVG document of monospace text cells. Each run of
+/// identically styled cells becomes one `<text>` element stretched to exactly
+/// its cell width, so alignment doesn't depend on the viewer's font.
+pub fn render(grid: &CellGrid, options: &SvgOptions) -> String {
+    let width = grid.width() as f32 * options.cell_width;
+    let height = grid.height() as f32 * options.cell_height;
+    // Put the baseline where a typical monospace font's descenders still fit
+    let baseline = options.cell_height * 0.8;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = fmt_px(width),
+        h = fmt_px(height)
+    );
+    if let Some(background) = grid.background {
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            background.to_hex()
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r#"<g font-family="{}" font-size="{}" xml:space="preserve">"#,
+        escape(&options.font_family),
+        fmt_px(options.font_size)
+    );
+
+    for (row_index, row) in grid.rows().iter().enumerate() {
+        let top = row_index as f32 * options.cell_height;
+        let mut column = 0;
+
+        for (style, text) in CellGrid::runs(row) {
+            let len = width::width(&text);
+            let x = column as f32 * options.cell_width;
+            column += len;
+
+            if let Some(bg) = style.bg {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    fmt_px(x),
+                    fmt_px(top),
+                    fmt_px(len as f32 * options.cell_width),
+                    fmt_px(options.cell_height),
+                    bg.to_hex()
+                );
+            }
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            let mut attrs = format!(
+                r#"x="{}" y="{}" textLength="{}" lengthAdjust="spacingAndGlyphs""#,
+                fmt_px(x),
+                fmt_px(top + baseline),
+                fmt_px(len as f32 * options.cell_width)
+            );
+            if let Some(fg) = style.fg {
+                let _ = write!(attrs, r#" fill="{}""#, fg.to_hex());
+            }
+            if style.bold {
+                attrs.push_str(r#" font-weight="bold""#);
+            }
+            if style.italic {
+                attrs.push_str(r#" font-style="italic""#);
+            }
+            let _ = writeln!(svg, "<text {}>{}</text>", attrs, escape(&text));
+        }
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// Format a pixel value without trailing zeros
+fn fmt_px(value: f32) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{CellStyle, Rgb};
+
+    #[test]
+    fn test_svg_dimensions_and_background() {
+        let mut grid = CellGrid::from_lines(&["ab", "abcd"], CellStyle::default());
+        grid.background = Some(Rgb::new(0, 0, 0));
+        let options = SvgOptions {
+            cell_width: 10.0,
+            cell_height: 20.0,
+            ..SvgOptions::default()
+        };
+
+        let svg = render(&grid, &options);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40""#));
+        assert!(svg.contains(r##"<rect width="100%" height="100%" fill="#000000"/>"##));
+    }
+
+    #[test]
+    fn test_svg_styles_and_escaping() {
+        let style = CellStyle::fg(Rgb::new(255, 0, 0)).bold().italic();
+        let grid = CellGrid::from_lines(&["<&>"], style);
+
+        let svg = render(&grid, &SvgOptions::default());
+        assert!(svg.contains(r##"fill="#ff0000" font-weight="bold" font-style="italic">&lt;&amp;&gt;</text>"##));
+    }
+
+    #[test]
+    fn test_blank_runs_are_skipped() {
+        let grid = CellGrid::from_lines(&["    "], CellStyle::default());
+        let svg = render(&grid, &SvgOptions::default());
+        assert!(!svg.contains("<text"));
+    }
+}
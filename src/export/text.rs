@@ -0,0 +1,73 @@
+use std::fmt::Write;
+
+use crate::grid::{CellGrid, CellStyle};
+
+/// Render the grid as text with all styling removed
+pub fn render_plain(grid: &CellGrid) -> String {
+    let mut text = String::new();
+    for row in grid.rows() {
+        let line: String = row.iter().map(|cell| cell.symbol.as_str()).collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Render the grid with 24-bit SGR escapes. Unlike terminal output this
+/// always styles, since the caller explicitly asked for escapes.
+pub fn render_ansi(grid: &CellGrid) -> String {
+    let mut text = String::new();
+    for row in grid.rows() {
+        for (style, run) in CellGrid::runs(row) {
+            match sgr(&style) {
+                Some(params) => {
+                    let _ = write!(text, "\x1b[{}m{}\x1b[0m", params, run);
+                }
+                None => text.push_str(&run),
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn sgr(style: &CellStyle) -> Option<String> {
+    let mut params = Vec::new();
+    if style.bold {
+        params.push("1".to_string());
+    }
+    if style.italic {
+        params.push("3".to_string());
+    }
+    if let Some(fg) = style.fg {
+        params.push(format!("38;2;{};{};{}", fg.r, fg.g, fg.b));
+    }
+    if let Some(bg) = style.bg {
+        params.push(format!("48;2;{};{};{}", bg.r, bg.g, bg.b));
+    }
+
+    if params.is_empty() {
+        None
+    } else {
+        Some(params.join(";"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Rgb;
+
+    #[test]
+    fn test_plain_strips_styles_and_trailing_space() {
+        let grid = CellGrid::from_lines(&["ab  ", " c"], CellStyle::fg(Rgb::WHITE).bold());
+        assert_eq!(render_plain(&grid), "ab\n c\n");
+    }
+
+    #[test]
+    fn test_ansi_escapes_each_run() {
+        let mut grid = CellGrid::from_lines(&["ab"], CellStyle::fg(Rgb::new(1, 2, 3)).bold());
+        grid.push_text("c", CellStyle::default());
+        assert_eq!(render_ansi(&grid), "\x1b[1;38;2;1;2;3mab\x1b[0m\nc\n");
+    }
+}
@@ -0,0 +1,249 @@
+//! Per-character font fallback: characters the primary font has no glyph
+//! for are drawn by the first fallback font that has one, or as a box.
+
+use crate::backend::{FontBackend, FontMetrics};
+use crate::grid::{CellGrid, CellStyle};
+use crate::width;
+
+/// Which font draws a run of characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// Index into the primary font followed by the fallbacks
+    Font(usize),
+    Placeholder,
+}
+
+/// The primary font with a chain of fallback fonts
+pub struct FallbackBackend {
+    /// The primary font first, then the fallbacks in order
+    fonts: Vec<Box<dyn FontBackend>>,
+}
+
+impl FallbackBackend {
+    pub fn new(primary: Box<dyn FontBackend>, fallbacks: Vec<Box<dyn FontBackend>>) -> Self {
+        let mut fonts = vec![primary];
+        fonts.extend(fallbacks);
+        Self { fonts }
+    }
+
+    /// The first font with glyphs for every character of a cluster
+    fn source(&self, grapheme: &str) -> Source {
+        self.fonts
+            .iter()
+            .position(|font| grapheme.chars().all(|ch| font.supports(ch)))
+            .map_or(Source::Placeholder, Source::Font)
+    }
+
+    fn handles(&self, source: Source, grapheme: &str) -> bool {
+        match source {
+            Source::Font(index) => grapheme.chars().all(|ch| self.fonts[index].supports(ch)),
+            Source::Placeholder => true,
+        }
+    }
+
+    /// Split a line into runs of grapheme clusters drawn by the same font.
+    /// Whitespace joins the following run, or the preceding one when that
+    /// font can't draw it, so spacing stays in the font being used.
+    fn runs(&self, line: &str) -> Vec<(Source, String)> {
+        let graphemes: Vec<&str> = width::graphemes(line).into_iter().map(|(g, _)| g).collect();
+        let sources: Vec<Option<Source>> = graphemes
+            .iter()
+            .map(|grapheme| (!grapheme.trim().is_empty()).then(|| self.source(grapheme)))
+            .collect();
+
+        let mut runs: Vec<(Source, String)> = Vec::new();
+        for (index, grapheme) in graphemes.iter().enumerate() {
+            let source = sources[index].unwrap_or_else(|| {
+                let next = sources[index..].iter().flatten().next().copied();
+                let previous = runs.last().map(|(source, _)| *source);
+                [next, previous]
+                    .into_iter()
+                    .flatten()
+                    .find(|&source| self.handles(source, grapheme))
+                    .unwrap_or(Source::Placeholder)
+            });
+            match runs.last_mut() {
+                Some((last, run)) if *last == source => run.push_str(grapheme),
+                _ => runs.push((source, grapheme.to_string())),
+            }
+        }
+        runs
+    }
+
+    fn render_line(&self, line: &str) -> CellGrid {
+        let height = self.metrics().height;
+        let blocks: Vec<CellGrid> = self
+            .runs(line)
+            .into_iter()
+            .map(|(source, run)| match source {
+                Source::Font(index) => self.fonts[index].render(&run),
+                Source::Placeholder => width::graphemes(&run).into_iter().fold(
+                    CellGrid::new(),
+                    |mut grid, (grapheme, columns)| {
+                        grid.place_beside(placeholder(grapheme, columns, height), 0);
+                        grid
+                    },
+                ),
+            })
+            .collect();
+
+        // Fonts of different heights are centered on each other
+        let tallest = blocks.iter().map(CellGrid::height).max().unwrap_or(0);
+        blocks.into_iter().fold(CellGrid::new(), |mut line, block| {
+            let mut centered = CellGrid::new();
+            for _ in 0..(tallest - block.height()) / 2 {
+                centered.push_row(Vec::new());
+            }
+            centered.append_below(block);
+            line.place_beside(centered, 0);
+            line
+        })
+    }
+}
+
+impl FontBackend for FallbackBackend {
+    fn name(&self) -> &str {
+        self.fonts[0].name()
+    }
+
+    fn render(&self, text: &str) -> CellGrid {
+        // Text the primary font covers keeps its own layout between lines
+        if text.lines().all(|line| self.handles(Source::Font(0), line)) {
+            return self.fonts[0].render(text);
+        }
+
+        let mut grid = CellGrid::new();
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                grid.push_row(Vec::new());
+            } else {
+                grid.append_below(self.render_line(line));
+            }
+        }
+        grid
+    }
+
+    fn metrics(&self) -> FontMetrics {
+        self.fonts[0].metrics()
+    }
+
+    fn supports(&self, ch: char) -> bool {
+        self.fonts.iter().any(|font| font.supports(ch))
+    }
+}
+
+/// A box the height of the primary font standing in for a cluster no font
+/// can draw, with the cluster itself inside, `columns` wide
+fn placeholder(grapheme: &str, columns: usize, height: usize) -> CellGrid {
+    let style = CellStyle::default();
+    if grapheme.trim().is_empty() {
+        return CellGrid::from_lines(&vec![" ".repeat(columns + 2); height.max(1)], style);
+    }
+    let edge = "─".repeat(columns);
+    let (top, bottom) = (format!("┌{}┐", edge), format!("└{}┘", edge));
+    let lines: Vec<String> = match height {
+        0 | 1 => vec![grapheme.to_string()],
+        2 => vec![format!("┌{}┐", grapheme), bottom],
+        _ => (0..height)
+            .map(|row| match row {
+                0 => top.clone(),
+                row if row == height - 1 => bottom.clone(),
+                row if row == (height - 1) / 2 => format!("│{}│", grapheme),
+                _ => format!("│{}│", " ".repeat(columns)),
+            })
+            .collect(),
+    };
+    CellGrid::from_lines(&lines, style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws the characters it has as a column of copies, `height` tall
+    struct Column {
+        glyphs: &'static str,
+        height: usize,
+    }
+
+    impl FontBackend for Column {
+        fn name(&self) -> &str {
+            self.glyphs
+        }
+
+        fn render(&self, text: &str) -> CellGrid {
+            let line: String = text.lines().collect();
+            CellGrid::from_lines(&vec![line; self.height], CellStyle::default())
+        }
+
+        fn metrics(&self) -> FontMetrics {
+            FontMetrics {
+                height: self.height,
+            }
+        }
+
+        fn supports(&self, ch: char) -> bool {
+            self.glyphs.contains(ch)
+        }
+    }
+
+    fn rows(grid: &CellGrid) -> Vec<String> {
+        grid.rows()
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.symbol.as_str()).collect())
+            .collect()
+    }
+
+    fn chain(fallbacks: &[(&'static str, usize)]) -> FallbackBackend {
+        let fallbacks = fallbacks
+            .iter()
+            .map(|&(glyphs, height)| Box::new(Column { glyphs, height }) as Box<dyn FontBackend>)
+            .collect();
+        FallbackBackend::new(Box::new(Column { glyphs: "Zo ", height: 3 }), fallbacks)
+    }
+
+    #[test]
+    fn test_missing_characters_use_the_fallback_chain() {
+        let font = chain(&[("ë", 1), ("ëП", 3)]);
+        assert_eq!(rows(&font.render("Zoë")), vec!["Zo", "Zoë", "Zo"]);
+        assert_eq!(rows(&font.render("Zo Пo")), vec!["Zo Пo"; 3]);
+        assert!(font.supports('П'));
+        assert!(!font.supports('?'));
+        assert_eq!(font.name(), "Zo ");
+    }
+
+    #[test]
+    fn test_characters_no_font_has_are_boxed() {
+        let font = chain(&[]);
+        assert_eq!(
+            rows(&font.render("Zo?\n\no")),
+            vec!["Zo┌─┐", "Zo│?│", "Zo└─┘", "", "o", "o", "o"]
+        );
+        assert_eq!(rows(&placeholder("?", 1, 2)), vec!["┌?┐", "└─┘"]);
+        assert_eq!(rows(&placeholder("?", 1, 1)), vec!["?"]);
+        // A wide cluster's box is two columns inside, with the continuation
+        let boxed = placeholder("日", 2, 3);
+        assert_eq!(rows(&boxed), vec!["┌──┐", "│日│", "└──┘"]);
+        assert!(boxed.rows().iter().all(|row| row.len() == 4));
+    }
+
+    #[test]
+    fn test_whitespace_stays_with_the_font_that_can_draw_it() {
+        // An accented letter goes whole to the font that has both parts
+        let font = chain(&[("e", 3), ("e\u{301}", 3)]);
+        assert_eq!(
+            font.runs("Zoe\u{301}"),
+            vec![(Source::Font(0), "Zo".to_string()), (Source::Font(2), "e\u{301}".to_string())]
+        );
+
+        let font = chain(&[("П", 3)]);
+        assert_eq!(
+            font.runs("Zo П o"),
+            vec![
+                (Source::Font(0), "Zo ".to_string()),
+                (Source::Font(1), "П".to_string()),
+                (Source::Font(0), " o".to_string()),
+            ]
+        );
+    }
+}
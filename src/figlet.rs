@@ -0,0 +1,654 @@
+//! FIGlet 2.2 font parsing and layout.
+//!
+//! Characters are fitted together the way the reference `figlet` does it:
+//! full width, kerning, or smushing by the six controlled rules (or
+//! universally when a font defines none), and the same for stacked lines
+//! with the five vertical rules.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use unicode_width::UnicodeWidthChar;
+
+use crate::backend::{FontBackend, FontMetrics};
+use crate::fonts;
+use crate::grid::{CellGrid, CellStyle};
+
+/// Characters every font defines, in order, before its code-tagged ones
+const REQUIRED: [u32; 7] = [196, 214, 220, 228, 246, 252, 223];
+
+// Horizontal smushing rules, as stored in the header layout bits
+const EQUAL: u32 = 1;
+const LOWLINE: u32 = 2;
+const HIERARCHY: u32 = 4;
+const PAIR: u32 = 8;
+const BIG_X: u32 = 16;
+const HARDBLANK: u32 = 32;
+const H_KERNING: u32 = 64;
+const H_SMUSHING: u32 = 128;
+
+// Vertical smushing rules, shifted down to start at bit 0
+const V_EQUAL: u32 = 1;
+const V_LOWLINE: u32 = 2;
+const V_HIERARCHY: u32 = 4;
+const V_HORIZONTAL_LINE: u32 = 8;
+const V_VERTICAL_LINE: u32 = 16;
+const V_FITTING: u32 = 8192;
+const V_SMUSHING: u32 = 16384;
+
+/// How characters are fitted together along one axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    /// Every character keeps its full width
+    FullWidth,
+    /// Characters move together until they touch
+    Kerning,
+    /// Characters overlap by one more sub-character where the rules allow
+    /// it; with no rules, any sub-character overlaps
+    Smushing(u32),
+}
+
+/// Horizontal and vertical fitting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub horizontal: Fit,
+    pub vertical: Fit,
+}
+
+/// Layout chosen on the command line instead of the font's own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LayoutMode {
+    /// Full width, like `figlet -W`
+    Full,
+    /// Fitting without overlap, like `figlet -k`
+    Kerning,
+    /// Smushing by the font's own rules, like `figlet -S`
+    Smush,
+    /// Smushing any sub-characters, ignoring the font's rules
+    Universal,
+}
+
+type Rows = Vec<Vec<char>>;
+
+/// A parsed FIGlet font
+#[derive(Debug, Clone)]
+pub struct FigFont {
+    hardblank: char,
+    height: usize,
+    right_to_left: bool,
+    layout: Layout,
+    /// Smushing rules of each axis, even when smushing is off by default
+    rules: (u32, u32),
+    glyphs: HashMap<char, Rows>,
+}
+
+impl FigFont {
+    pub fn parse(content: &str) -> Result<Self> {
+        let header = fonts::parse_header(content)?;
+        if header.height == 0 {
+            return Err(anyhow!("FIGlet font has no rows"));
+        }
+
+        let (layout, rules) = header_layout(header.old_layout, header.full_layout);
+        let mut font = FigFont {
+            hardblank: header.hardblank,
+            height: header.height,
+            right_to_left: header.print_direction == 1,
+            layout,
+            rules,
+            glyphs: HashMap::new(),
+        };
+
+        let mut lines = content.lines().skip(1 + header.comment.len());
+        // Truncated fonts keep the characters they do define
+        for code in (32..127).chain(REQUIRED) {
+            let Some(rows) = font.read_glyph(&mut lines)? else {
+                return Ok(font);
+            };
+            font.insert(code, rows);
+        }
+
+        while let Some(tag) = lines.next() {
+            let code = tag.split_whitespace().next().unwrap_or_default();
+            let Some(rows) = font.read_glyph(&mut lines)? else {
+                break;
+            };
+            // Negative codes index translation tables and can't be typed
+            if code.starts_with('-') {
+                continue;
+            }
+            let code = parse_code(code).ok_or_else(|| anyhow!("Invalid character code '{}'", code))?;
+            font.insert(code, rows);
+        }
+        Ok(font)
+    }
+
+    /// The layout the font's header asks for
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Layout for a mode picked by the user
+    pub fn layout_for(&self, mode: LayoutMode) -> Layout {
+        let both = |fit| Layout { horizontal: fit, vertical: fit };
+        match mode {
+            LayoutMode::Full => both(Fit::FullWidth),
+            LayoutMode::Kerning => both(Fit::Kerning),
+            LayoutMode::Smush => Layout {
+                horizontal: Fit::Smushing(self.rules.0),
+                vertical: Fit::Smushing(self.rules.1),
+            },
+            LayoutMode::Universal => both(Fit::Smushing(0)),
+        }
+    }
+
+    /// Render text, one FIGlet line per input line. Consecutive lines are
+    /// fitted by the vertical layout; a blank input line separates them with
+    /// an empty row, and lines the font has no characters for stay plain.
+    pub fn render(&self, text: &str, layout: Layout) -> Vec<String> {
+        let mut rows: Rows = Vec::new();
+        let mut paragraph: Option<Rows> = None;
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                rows.extend(paragraph.take().unwrap_or_default());
+                rows.push(Vec::new());
+                continue;
+            }
+
+            let block = self
+                .render_line(line, layout.horizontal)
+                .unwrap_or_else(|| vec![line.chars().collect()]);
+            paragraph = Some(match paragraph.take() {
+                Some(upper) => self.stack(upper, block, layout.vertical),
+                None => block,
+            });
+        }
+        rows.extend(paragraph.unwrap_or_default());
+
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|ch| if ch == self.hardblank { ' ' } else { ch })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn read_glyph<'a>(&self, lines: &mut impl Iterator<Item = &'a str>) -> Result<Option<Rows>> {
+        let mut rows: Rows = lines
+            .take(self.height)
+            .map(|line| strip_endmark(line).chars().collect())
+            .collect();
+        if rows.len() < self.height {
+            return Ok(None);
+        }
+        // Layout works in sub-characters, so each must fill exactly one column
+        if let Some(&ch) = rows.iter().flatten().find(|ch| ch.width().unwrap_or(1) != 1) {
+            return Err(anyhow!(
+                "Font uses '{}' (U+{:04X}), which isn't one column wide; only single-width sub-characters are supported",
+                ch,
+                ch as u32
+            ));
+        }
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize(width, ' ');
+        }
+        Ok(Some(rows))
+    }
+
+    fn insert(&mut self, code: u32, rows: Rows) {
+        if let Some(ch) = char::from_u32(code) {
+            self.glyphs.insert(ch, rows);
+        }
+    }
+
+    /// Glyph for a character, or the font's "missing character" glyph
+    fn glyph(&self, ch: char) -> Option<&Rows> {
+        self.glyphs.get(&ch).or_else(|| self.glyphs.get(&'\0'))
+    }
+
+    fn render_line(&self, line: &str, fit: Fit) -> Option<Rows> {
+        let mut chars: Vec<char> = line.chars().collect();
+        if self.right_to_left {
+            chars.reverse();
+        }
+
+        let mut output: Rows = vec![Vec::new(); self.height];
+        let mut previous_width = 0;
+        let mut rendered = false;
+        for glyph in chars.into_iter().filter_map(|ch| self.glyph(ch)) {
+            let width = glyph[0].len();
+            let amount = self.smush_amount(&output, glyph, fit, previous_width);
+            for (row, glyph_row) in output.iter_mut().zip(glyph) {
+                let start = row.len();
+                for (k, &right) in glyph_row.iter().enumerate().take(amount) {
+                    if let Some(column) = (start + k).checked_sub(amount) {
+                        row[column] = self
+                            .smush(row[column], right, fit, previous_width, width)
+                            .unwrap_or(right);
+                    }
+                }
+                row.extend(glyph_row.iter().skip(amount));
+            }
+            previous_width = width;
+            rendered = true;
+        }
+
+        rendered.then_some(output)
+    }
+
+    /// Columns the next glyph can overlap the line so far, as in `figlet`'s
+    /// `smushamt`
+    fn smush_amount(&self, output: &Rows, glyph: &Rows, fit: Fit, previous_width: usize) -> usize {
+        if fit == Fit::FullWidth {
+            return 0;
+        }
+
+        let width = glyph[0].len();
+        let mut amount = width as isize;
+        for (row, glyph_row) in output.iter().zip(glyph) {
+            let line_end = row.iter().rposition(|&ch| ch != ' ');
+            let left = line_end.map(|index| row[index]);
+            let char_start = glyph_row.iter().position(|&ch| ch != ' ');
+
+            let mut row_amount = char_start.unwrap_or(width) as isize + row.len() as isize
+                - 1
+                - line_end.unwrap_or(0) as isize;
+            match (left, char_start.map(|index| glyph_row[index])) {
+                (None, _) => row_amount += 1,
+                (Some(left), Some(right)) => {
+                    if self.smush(left, right, fit, previous_width, width).is_some() {
+                        row_amount += 1;
+                    }
+                }
+                (Some(_), None) => {}
+            }
+            amount = amount.min(row_amount);
+        }
+        amount.max(0) as usize
+    }
+
+    /// The sub-character two overlapping ones smush into, if any
+    fn smush(&self, left: char, right: char, fit: Fit, previous_width: usize, width: usize) -> Option<char> {
+        if left == ' ' {
+            return Some(right);
+        }
+        if right == ' ' {
+            return Some(left);
+        }
+        if previous_width < 2 || width < 2 {
+            return None;
+        }
+        let Fit::Smushing(rules) = fit else {
+            return None;
+        };
+
+        if rules == 0 {
+            // Universal smushing keeps visible sub-characters, then the later one
+            return Some(if left == self.hardblank {
+                right
+            } else if right == self.hardblank || self.right_to_left {
+                left
+            } else {
+                right
+            });
+        }
+
+        if rules & HARDBLANK != 0 && left == self.hardblank && right == self.hardblank {
+            return Some(left);
+        }
+        if left == self.hardblank || right == self.hardblank {
+            return None;
+        }
+        if rules & EQUAL != 0 && left == right {
+            return Some(left);
+        }
+        if rules & LOWLINE != 0 {
+            if let Some(ch) = lowline(left, right) {
+                return Some(ch);
+            }
+        }
+        if rules & HIERARCHY != 0 {
+            if let Some(ch) = hierarchy(left, right) {
+                return Some(ch);
+            }
+        }
+        if rules & PAIR != 0 {
+            let pair = [left, right];
+            if ["[]", "{}", "()"]
+                .iter()
+                .any(|brackets| pair.iter().all(|ch| brackets.contains(*ch)) && left != right)
+            {
+                return Some('|');
+            }
+        }
+        if rules & BIG_X != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Put one FIGlet line below another, overlapping rows as the vertical
+    /// fit allows
+    fn stack(&self, mut upper: Rows, lower: Rows, fit: Fit) -> Rows {
+        let overlap = self.vertical_overlap(&upper, &lower, fit);
+        let first = upper.len() - overlap;
+        for (row, bottom) in upper[first..].iter_mut().zip(&lower) {
+            let width = row.len().max(bottom.len());
+            row.resize(width, ' ');
+            for (column, cell) in row.iter_mut().enumerate() {
+                let below = bottom.get(column).copied().unwrap_or(' ');
+                *cell = self.vertical_smush(*cell, below, fit);
+            }
+        }
+        upper.extend(lower.into_iter().skip(overlap));
+        upper
+    }
+
+    fn vertical_overlap(&self, upper: &Rows, lower: &Rows, fit: Fit) -> usize {
+        if fit == Fit::FullWidth {
+            return 0;
+        }
+
+        let mut overlap = 0;
+        for candidate in 1..=upper.len().min(lower.len()) {
+            let mut ends = false;
+            let top = &upper[upper.len() - candidate..];
+            for (top_row, bottom_row) in top.iter().zip(lower) {
+                match self.vertical_check(top_row, bottom_row, fit) {
+                    Overlap::Invalid => return overlap,
+                    Overlap::Last => ends = true,
+                    Overlap::Valid => {}
+                }
+            }
+            overlap = candidate;
+            if ends {
+                break;
+            }
+        }
+        overlap
+    }
+
+    /// Whether two rows can overlap, and whether smushing them stops any
+    /// further overlap
+    fn vertical_check(&self, top: &[char], bottom: &[char], fit: Fit) -> Overlap {
+        let mut last = false;
+        for (&above, &below) in top.iter().zip(bottom) {
+            if above == ' ' || below == ' ' {
+                continue;
+            }
+            match fit {
+                Fit::Smushing(0) => return Overlap::Last,
+                Fit::Smushing(rules) => {
+                    // Vertical lines supersmush through any number of rows
+                    if rules & V_VERTICAL_LINE != 0 && above == '|' && below == '|' {
+                        continue;
+                    }
+                    if vertical_rule(above, below, rules).is_none() {
+                        return Overlap::Invalid;
+                    }
+                    last = true;
+                }
+                _ => return Overlap::Invalid,
+            }
+        }
+        if last {
+            Overlap::Last
+        } else {
+            Overlap::Valid
+        }
+    }
+
+    fn vertical_smush(&self, above: char, below: char, fit: Fit) -> char {
+        if let Fit::Smushing(rules) = fit {
+            if rules != 0 && above != ' ' && below != ' ' {
+                if rules & V_VERTICAL_LINE != 0 && above == '|' && below == '|' {
+                    return '|';
+                }
+                if let Some(ch) = vertical_rule(above, below, rules) {
+                    return ch;
+                }
+            }
+        }
+        // The lower sub-character wins unless it's blank
+        if below == ' ' || (below == self.hardblank && above != ' ') {
+            above
+        } else {
+            below
+        }
+    }
+}
+
+/// A FIGlet or TOIlet font laid out with a fixed layout
+pub struct FigletBackend {
+    name: String,
+    font: FigFont,
+    layout: Layout,
+}
+
+impl FigletBackend {
+    /// Use the font's own layout unless a mode is given
+    pub fn new(name: &str, font: FigFont, mode: Option<LayoutMode>) -> Self {
+        let layout = match mode {
+            Some(mode) => font.layout_for(mode),
+            None => font.layout(),
+        };
+        Self {
+            name: name.to_string(),
+            font,
+            layout,
+        }
+    }
+}
+
+impl FontBackend for FigletBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn render(&self, text: &str) -> CellGrid {
+        CellGrid::from_lines(&self.font.render(text, self.layout), CellStyle::default())
+    }
+
+    fn metrics(&self) -> FontMetrics {
+        FontMetrics {
+            height: self.font.height,
+        }
+    }
+
+    fn supports(&self, ch: char) -> bool {
+        self.font.glyphs.contains_key(&ch)
+    }
+}
+
+enum Overlap {
+    Valid,
+    Last,
+    Invalid,
+}
+
+/// Default layout and rules from the header's old and full layout fields
+fn header_layout(old_layout: i32, full_layout: Option<u32>) -> (Layout, (u32, u32)) {
+    let Some(full) = full_layout else {
+        let horizontal = match old_layout {
+            layout if layout < 0 => Fit::FullWidth,
+            0 => Fit::Kerning,
+            rules => Fit::Smushing(rules as u32 & 63),
+        };
+        let rules = (old_layout.max(0) as u32 & 63, 0);
+        return (Layout { horizontal, vertical: Fit::FullWidth }, rules);
+    };
+
+    let rules = (full & 63, (full >> 8) & 31);
+    let horizontal = if full & H_SMUSHING != 0 {
+        Fit::Smushing(rules.0)
+    } else if full & H_KERNING != 0 {
+        Fit::Kerning
+    } else {
+        Fit::FullWidth
+    };
+    let vertical = if full & V_SMUSHING != 0 {
+        Fit::Smushing(rules.1)
+    } else if full & V_FITTING != 0 {
+        Fit::Kerning
+    } else {
+        Fit::FullWidth
+    };
+    (Layout { horizontal, vertical }, rules)
+}
+
+/// Decimal, `0x` hexadecimal or leading-zero octal character code
+fn parse_code(code: &str) -> Option<u32> {
+    if let Some(hex) = code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if code.len() > 1 && code.starts_with('0') {
+        u32::from_str_radix(&code[1..], 8).ok()
+    } else {
+        code.parse().ok()
+    }
+}
+
+/// Drop trailing whitespace and the run of endmark characters
+fn strip_endmark(line: &str) -> &str {
+    let line = line.trim_end();
+    match line.chars().last() {
+        Some(endmark) => line.trim_end_matches(endmark),
+        None => line,
+    }
+}
+
+/// An underscore gives way to a bracket or line
+fn lowline(left: char, right: char) -> Option<char> {
+    const REPLACES: &str = "|/\\[]{}()<>";
+    if left == '_' && REPLACES.contains(right) {
+        Some(right)
+    } else if right == '_' && REPLACES.contains(left) {
+        Some(left)
+    } else {
+        None
+    }
+}
+
+/// The sub-character from the higher class wins: | /\ [] {} () <>
+fn hierarchy(left: char, right: char) -> Option<char> {
+    const CLASSES: [&str; 6] = ["|", "/\\", "[]", "{}", "()", "<>"];
+    let class = |ch: char| CLASSES.iter().position(|class| class.contains(ch));
+    match (class(left), class(right)) {
+        (Some(l), Some(r)) if l < r => Some(right),
+        (Some(l), Some(r)) if r < l => Some(left),
+        _ => None,
+    }
+}
+
+fn vertical_rule(above: char, below: char, rules: u32) -> Option<char> {
+    if rules & V_EQUAL != 0 && above == below {
+        return Some(above);
+    }
+    if rules & V_LOWLINE != 0 {
+        if let Some(ch) = lowline(above, below) {
+            return Some(ch);
+        }
+    }
+    if rules & V_HIERARCHY != 0 {
+        if let Some(ch) = hierarchy(above, below) {
+            return Some(ch);
+        }
+    }
+    if rules & V_HORIZONTAL_LINE != 0 && matches!((above, below), ('-', '_') | ('_', '-')) {
+        return Some('=');
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standard() -> FigFont {
+        FigFont::parse(fonts::default_font().text().unwrap()).unwrap()
+    }
+
+    fn render(font: &FigFont, text: &str, mode: LayoutMode) -> String {
+        font.render(text, font.layout_for(mode)).join("\n")
+    }
+
+    #[test]
+    fn test_horizontal_layouts_match_figlet() {
+        let font = standard();
+        assert_eq!(font.layout(), font.layout_for(LayoutMode::Smush));
+        assert_eq!(
+            font.render("Hi", font.layout()).join("\n"),
+            " _   _ _ \n| | | (_)\n| |_| | |\n|  _  | |\n|_| |_|_|\n         "
+        );
+        assert_eq!(
+            render(&font, "Hi", LayoutMode::Kerning),
+            " _   _  _ \n| | | |(_)\n| |_| || |\n|  _  || |\n|_| |_||_|\n          "
+        );
+        assert_eq!(
+            render(&font, "Hi", LayoutMode::Full),
+            "  _   _   _ \n | | | | (_)\n | |_| | | |\n |  _  | | |\n |_| |_| |_|\n            "
+        );
+    }
+
+    #[test]
+    fn test_smushing_rules() {
+        let font = standard();
+        let smush = |left, right, rules| font.smush(left, right, Fit::Smushing(rules), 2, 2);
+        assert_eq!(smush('|', '|', EQUAL), Some('|'));
+        assert_eq!(smush('_', '/', LOWLINE), Some('/'));
+        assert_eq!(smush('(', '|', HIERARCHY), Some('('));
+        assert_eq!(smush(']', '[', PAIR), Some('|'));
+        assert_eq!(smush('\\', '/', BIG_X), Some('Y'));
+        assert_eq!(smush('$', '$', HARDBLANK), Some('$'));
+        assert_eq!(smush('$', 'x', EQUAL), None);
+        assert_eq!(smush('a', 'b', 0), Some('b'));
+        assert_eq!(smush('a', 'b', EQUAL), None);
+        assert_eq!(font.smush('a', 'b', Fit::Smushing(0), 1, 2), None);
+    }
+
+    #[test]
+    fn test_vertical_layouts() {
+        // A dash on the bottom row and an underscore on the top row
+        let blank = " @\n @@\n";
+        let content = "flf2a$ 2 1 4 0 0 0 24463\n".to_string()
+            + &blank.repeat(13)
+            + " @\n-@@\n"
+            + &blank.repeat(49)
+            + "_@\n @@\n"
+            + &blank.repeat(38);
+        let font = FigFont::parse(&content).unwrap();
+        assert_eq!(font.layout().vertical, Fit::Smushing(31));
+
+        let stacked = |mode| font.render("-\n_", font.layout_for(mode));
+        assert_eq!(stacked(LayoutMode::Full), vec![" ", "-", "_", " "]);
+        assert_eq!(stacked(LayoutMode::Kerning), vec![" ", "-", "_", " "]);
+        assert_eq!(stacked(LayoutMode::Smush), vec![" ", "=", " "]);
+        assert_eq!(stacked(LayoutMode::Universal), vec![" ", "_", " "]);
+        assert_eq!(font.render("-\n\n_", font.layout()), vec![" ", "-", "", "_", " "]);
+    }
+
+    #[test]
+    fn test_parse_skips_negative_codes_and_truncation() {
+        let big = FigFont::parse(fonts::builtin("big").unwrap().text().unwrap()).unwrap();
+        assert!(big.glyphs.contains_key(&'\u{391}'));
+
+        let truncated = FigFont::parse("flf2a$ 1 1 2 -1 0\n$@@\n!@@\n").unwrap();
+        assert_eq!(truncated.layout().horizontal, Fit::FullWidth);
+        assert_eq!(truncated.render("! ?", truncated.layout()), vec!["! "]);
+        assert_eq!(truncated.render("?", truncated.layout()), vec!["?"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_wide_sub_characters() {
+        let error = FigFont::parse("tlf2a$ 1 1 3 -1 0\n$@@\n中@@\n").unwrap_err();
+        assert!(error.to_string().contains("U+4E2D"));
+
+        let error = FigFont::parse("tlf2a$ 1 1 3 -1 0\n$@@\ne\u{301}@@\n").unwrap_err();
+        assert!(error.to_string().contains("U+0301"));
+    }
+}
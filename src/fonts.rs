@@ -0,0 +1,475 @@
+use anyhow::{anyhow, Context, Result};
+use flate2::read::DeflateDecoder;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::backend::{FontBackend, FontOptions};
+use crate::bitmap::{BitmapBackend, BitmapFont};
+use crate::figlet::{FigFont, FigletBackend};
+
+/// Font used when none is configured or the requested one can't be found
+pub const DEFAULT_FONT: &str = "standard";
+
+/// Every `fonts/*.flf` and `*.tlf` in the source tree, deflated by `build.rs`
+const BUILTIN: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
+
+/// Extensions of FIGlet and TOIlet font files
+const FONT_EXTENSIONS: [&str; 2] = ["flf", "tlf"];
+
+/// Extensions of BDF and PSF bitmap font files, which may also be gzipped
+const BITMAP_EXTENSIONS: [&str; 3] = ["bdf", "psf", "psfu"];
+
+/// Signatures of a ZIP local file header, central directory entry and end
+/// of central directory record
+const ZIP_LOCAL: &[u8] = b"PK\x03\x04";
+const ZIP_ENTRY: &[u8] = b"PK\x01\x02";
+const ZIP_END: &[u8] = b"PK\x05\x06";
+
+/// Details from a FIGlet or TOIlet font's header line and comment block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontHeader {
+    /// Sub-character drawn as a space that smushing never overlaps
+    pub hardblank: char,
+    /// Rows in every character
+    pub height: usize,
+    /// Horizontal layout of fonts without a full layout: -1 for full width,
+    /// 0 for kerning, otherwise the smushing rules
+    pub old_layout: i32,
+    /// 1 when the font is written right to left
+    pub print_direction: i32,
+    /// Horizontal and vertical layout bits, when the header has them
+    pub full_layout: Option<u32>,
+    pub comment: Vec<String>,
+}
+
+impl FontHeader {
+    /// First non-empty comment line, which by convention credits the author
+    pub fn credit(&self) -> Option<&str> {
+        self.comment
+            .iter()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+    }
+}
+
+/// Parse the `flf2a` header of a FIGlet font, or the `tlf2a` header of a
+/// TOIlet font, which has the same layout
+pub fn parse_header(content: &str) -> Result<FontHeader> {
+    let mut lines = content.lines();
+    let first = lines.next().unwrap_or_default();
+    if !first.starts_with("flf2a") && !first.starts_with("tlf2a") {
+        return Err(anyhow!("Not a FIGlet font: missing flf2a or tlf2a signature"));
+    }
+
+    let fields: Vec<&str> = first.split_whitespace().collect();
+    let field = |index: usize, name: &str| -> Result<&str> {
+        fields
+            .get(index)
+            .copied()
+            .ok_or_else(|| anyhow!("FIGlet header is missing the {}", name))
+    };
+    let number = |index: usize, name: &str| -> Result<usize> {
+        field(index, name)?
+            .parse()
+            .context(format!("Invalid {} in FIGlet header", name))
+    };
+    let height = number(1, "height")?;
+    let old_layout = field(4, "layout")?
+        .parse()
+        .context("Invalid layout in FIGlet header")?;
+    let comment_lines = number(5, "comment line count")?;
+    let optional = |index: usize| fields.get(index).and_then(|value| value.parse().ok());
+
+    Ok(FontHeader {
+        hardblank: first.chars().nth(5).unwrap_or('$'),
+        height,
+        old_layout,
+        print_direction: optional(6).unwrap_or(0),
+        full_layout: optional(7).and_then(|layout| u32::try_from(layout).ok()),
+        comment: lines.take(comment_lines).map(str::to_string).collect(),
+    })
+}
+
+/// Where a font was loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    Builtin,
+    File(PathBuf),
+}
+
+impl FontSource {
+    pub fn describe(&self) -> String {
+        match self {
+            FontSource::Builtin => "built-in".to_string(),
+            FontSource::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// What a font file holds
+#[derive(Debug, Clone)]
+enum FontData {
+    /// A FIGlet or TOIlet font as text
+    Figlet(String),
+    /// A BDF or PSF font, parsed when it's drawn
+    Bitmap(Vec<u8>),
+}
+
+/// A FIGlet or bitmap font and its source
+#[derive(Debug, Clone)]
+pub struct Font {
+    pub name: String,
+    pub source: FontSource,
+    data: FontData,
+}
+
+impl Font {
+    /// The font file as written
+    pub fn bytes(&self) -> &[u8] {
+        match &self.data {
+            FontData::Figlet(content) => content.as_bytes(),
+            FontData::Bitmap(bytes) => bytes,
+        }
+    }
+
+    /// The font file's text, for FIGlet and TOIlet fonts
+    pub fn text(&self) -> Option<&str> {
+        match &self.data {
+            FontData::Figlet(content) => Some(content),
+            FontData::Bitmap(_) => None,
+        }
+    }
+
+    pub fn header(&self) -> Result<FontHeader> {
+        let content = self
+            .text()
+            .ok_or_else(|| anyhow!("'{}' is a bitmap font, not a FIGlet font", self.name))?;
+        parse_header(content)
+    }
+
+    /// Who made the font: a FIGlet font's credit line or a BDF font's
+    /// copyright
+    pub fn credit(&self) -> Option<String> {
+        match &self.data {
+            FontData::Figlet(_) => self
+                .header()
+                .ok()
+                .and_then(|header| header.credit().map(str::to_string)),
+            FontData::Bitmap(bytes) => BitmapFont::parse(bytes)
+                .ok()
+                .and_then(|font| font.description().map(str::to_string)),
+        }
+    }
+
+    /// Parse the font into a backend the renderer can draw with
+    pub fn backend(&self, options: &FontOptions) -> Result<Box<dyn FontBackend>> {
+        let failed = |e: anyhow::Error| anyhow!("Failed to load font '{}': {}", self.name, e);
+        Ok(match &self.data {
+            FontData::Figlet(content) => {
+                let font = FigFont::parse(content).map_err(failed)?;
+                Box::new(FigletBackend::new(&self.name, font, options.layout))
+            }
+            FontData::Bitmap(bytes) => {
+                let font = BitmapFont::parse(bytes).map_err(failed)?;
+                Box::new(BitmapBackend::new(&self.name, font, options.pixel))
+            }
+        })
+    }
+}
+
+/// Look up a font compiled into the binary
+pub fn builtin(name: &str) -> Option<Font> {
+    BUILTIN
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(name, compressed)| Font {
+            name: name.to_string(),
+            source: FontSource::Builtin,
+            data: FontData::Figlet(inflate(name, compressed)),
+        })
+}
+
+/// Every font compiled into the binary, by name
+pub fn builtins() -> Vec<Font> {
+    BUILTIN.iter().filter_map(|(name, _)| builtin(name)).collect()
+}
+
+fn inflate(name: &str, compressed: &[u8]) -> String {
+    let mut content = String::new();
+    DeflateDecoder::new(compressed)
+        .read_to_string(&mut content)
+        .unwrap_or_else(|e| panic!("built-in font '{}' is corrupt: {}", name, e));
+    content
+}
+
+/// The font used when nothing else matches
+pub fn default_font() -> Font {
+    builtin(DEFAULT_FONT).expect("the default font is built in")
+}
+
+/// FIGlet, TOIlet and bitmap fonts in the user's font directory, by name
+pub fn user_fonts(dir: &Path) -> Vec<Font> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut fonts: Vec<Font> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| load(&entry.path()))
+        .collect();
+    fonts.sort_by(|a, b| a.name.cmp(&b.name));
+    fonts
+}
+
+/// Load a font file, named after the file without its extensions, or
+/// `None` when it isn't a font file
+fn load(path: &Path) -> Option<Font> {
+    let file_name = path.file_name()?.to_str()?;
+    let unzipped = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    let (name, extension) = unzipped.rsplit_once('.')?;
+
+    let bytes = fs::read(path).ok()?;
+    let data = if FONT_EXTENSIONS.contains(&extension) && unzipped == file_name {
+        FontData::Figlet(decode(&bytes).ok()?)
+    } else if BITMAP_EXTENSIONS.contains(&extension) {
+        FontData::Bitmap(bytes)
+    } else {
+        return None;
+    };
+    Some(Font {
+        name: name.to_string(),
+        source: FontSource::File(path.to_path_buf()),
+        data,
+    })
+}
+
+/// Text of a font file. Zipped fonts are unpacked, as FIGlet allows, and
+/// files that aren't UTF-8 are read as Latin-1 like older FIGlet fonts.
+pub fn decode(bytes: &[u8]) -> Result<String> {
+    let bytes = if bytes.starts_with(ZIP_LOCAL) {
+        unzip_first(bytes)?
+    } else {
+        bytes.to_vec()
+    };
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => e.into_bytes().into_iter().map(char::from).collect(),
+    })
+}
+
+/// The first file in a ZIP archive, which is where FIGlet looks for the font
+fn unzip_first(bytes: &[u8]) -> Result<Vec<u8>> {
+    let truncated = || anyhow!("Zipped font is truncated");
+    let u16_at = |offset: usize| -> Result<usize> {
+        let field = bytes.get(offset..offset + 2).ok_or_else(truncated)?;
+        Ok(u16::from_le_bytes([field[0], field[1]]) as usize)
+    };
+    let u32_at = |offset: usize| -> Result<usize> {
+        let field = bytes.get(offset..offset + 4).ok_or_else(truncated)?;
+        Ok(u32::from_le_bytes([field[0], field[1], field[2], field[3]]) as usize)
+    };
+
+    // Sizes live in the central directory; local headers may leave them out
+    let end = bytes
+        .windows(ZIP_END.len())
+        .rposition(|window| window == ZIP_END)
+        .ok_or_else(|| anyhow!("Zipped font has no central directory"))?;
+    if u16_at(end + 10)? == 0 {
+        return Err(anyhow!("Zipped font is empty"));
+    }
+    let entry = u32_at(end + 16)?;
+    if bytes.get(entry..entry + 4) != Some(ZIP_ENTRY) {
+        return Err(anyhow!("Zipped font has a corrupt central directory"));
+    }
+    let method = u16_at(entry + 10)?;
+    let size = u32_at(entry + 20)?;
+    let local = u32_at(entry + 42)?;
+    let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+    let data = bytes.get(start..start + size).ok_or_else(truncated)?;
+
+    match method {
+        0 => Ok(data.to_vec()),
+        8 => {
+            let mut content = Vec::new();
+            DeflateDecoder::new(data)
+                .read_to_end(&mut content)
+                .context("Failed to unzip font")?;
+            Ok(content)
+        }
+        other => Err(anyhow!("Zipped font uses unsupported compression method {}", other)),
+    }
+}
+
+/// Every font that can be selected. User fonts replace built-ins of the
+/// same name; the rest follow the built-ins.
+pub fn available(dir: &Path) -> Vec<Font> {
+    let mut user = user_fonts(dir);
+    let mut fonts: Vec<Font> = builtins()
+        .into_iter()
+        .map(|font| match user.iter().position(|mine| mine.name == font.name) {
+            Some(index) => user.remove(index),
+            None => font,
+        })
+        .collect();
+    fonts.extend(user);
+    fonts
+}
+
+/// Find a font by name, preferring the user's fonts over the built-ins. A
+/// path to a font file, such as a console font, is loaded directly.
+pub fn find(name: &str, dir: &Path) -> Option<Font> {
+    if is_path(name) {
+        if let Some(font) = load(Path::new(name)) {
+            return Some(font);
+        }
+    }
+    user_fonts(dir)
+        .into_iter()
+        .find(|font| font.name == name)
+        .or_else(|| builtin(name))
+}
+
+/// Whether a font name is written as a path: it has a directory or a font
+/// file extension, so a stray file named like a font isn't picked up
+pub fn is_path(name: &str) -> bool {
+    let unzipped = name.strip_suffix(".gz").unwrap_or(name);
+    let extension = unzipped.rsplit_once('.').map(|(_, extension)| extension);
+    name.chars().any(std::path::is_separator)
+        || extension.is_some_and(|extension| {
+            FONT_EXTENSIONS.contains(&extension) || BITMAP_EXTENSIONS.contains(&extension)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_header() {
+        let header = parse_header(include_str!("../fonts/small.flf")).unwrap();
+        assert_eq!(header.height, 5);
+        assert_eq!(header.credit(), Some("Small by Glenn Chappell 4/93 -- based on Standard"));
+
+        assert!(parse_header("not a font").is_err());
+        assert!(parse_header("flf2a$ x").is_err());
+    }
+
+    #[test]
+    fn test_builtin_fonts_parse() {
+        let names: Vec<String> = builtins().into_iter().map(|font| font.name).collect();
+        for name in ["standard", "small", "big", "banner", "block", "chrome", "pixel"] {
+            assert!(names.iter().any(|builtin| builtin == name), "{} is not built in", name);
+        }
+        for font in builtins() {
+            assert!(font.header().unwrap().height > 0, "{} has no height", font.name);
+        }
+    }
+
+    #[test]
+    fn test_builtin_fonts_load() {
+        for font in builtins() {
+            assert!(
+                FigFont::parse(font.text().unwrap()).is_ok(),
+                "{} failed to load",
+                font.name
+            );
+            // A built-in font has to be able to draw any typed greeting
+            let backend = font.backend(&FontOptions::default()).unwrap();
+            let missing: String = (' '..='~').filter(|&ch| !backend.supports(ch)).collect();
+            assert!(missing.is_empty(), "{} has no glyphs for {:?}", font.name, missing);
+        }
+    }
+
+    #[test]
+    fn test_user_fonts_are_found_after_builtins() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("mine.flf"), "flf2a$ 2 1 4 0 1\nby me\n").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "not a font").unwrap();
+
+        let names: Vec<String> = available(temp_dir.path())
+            .into_iter()
+            .map(|font| font.name)
+            .collect();
+        assert_eq!(names.last().map(String::as_str), Some("mine"));
+        assert_eq!(names.len(), BUILTIN.len() + 1);
+
+        let mine = find("mine", temp_dir.path()).unwrap();
+        assert_eq!(mine.source, FontSource::File(temp_dir.path().join("mine.flf")));
+    }
+
+    #[test]
+    fn test_paths_load_directly_and_names_fall_through() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("mine.flf");
+        fs::write(&path, "flf2a$ 2 1 4 0 1\nby me\n").unwrap();
+
+        let mine = find(path.to_str().unwrap(), Path::new("/nonexistent")).unwrap();
+        assert_eq!(mine.source, FontSource::File(path));
+
+        assert!(is_path("fonts/mine"));
+        assert!(is_path("Lat15-Terminus16.psf.gz"));
+        assert!(!is_path("small"));
+        assert!(!is_path("my.font"));
+
+        // A name that only looks like a path falls back to the font of that name
+        fs::write(temp_dir.path().join("odd.flf.flf"), "flf2a$ 2 1 4 0 1\nby me\n").unwrap();
+        let odd = find("odd.flf", temp_dir.path()).unwrap();
+        assert_eq!(odd.source, FontSource::File(temp_dir.path().join("odd.flf.flf")));
+    }
+
+    /// A ZIP archive holding `data` uncompressed
+    fn stored_zip(name: &str, data: &[u8]) -> Vec<u8> {
+        let sizes = [(data.len() as u32).to_le_bytes(); 2].concat();
+        let name_length = (name.len() as u16).to_le_bytes();
+
+        let mut zip = [ZIP_LOCAL, &[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]].concat();
+        zip.extend([&sizes[..], &name_length, &[0, 0], name.as_bytes(), data].concat());
+        let directory = zip.len() as u32;
+        zip.extend([ZIP_ENTRY, &[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]].concat());
+        zip.extend([&sizes[..], &name_length, &[0; 12], &[0; 4]].concat());
+        zip.extend(name.as_bytes());
+        let directory_size = zip.len() as u32 - directory;
+        zip.extend([ZIP_END, &[0, 0, 0, 0, 1, 0, 1, 0]].concat());
+        zip.extend([directory_size.to_le_bytes(), directory.to_le_bytes()].concat());
+        zip.extend([0, 0]);
+        zip
+    }
+
+    #[test]
+    fn test_zipped_toilet_fonts_load() {
+        let mut tlf = "tlf2a$ 1 1 3 -1 1\nmini by me\n".to_string();
+        for code in (32..127).chain([196, 214, 220, 228, 246, 252, 223]) {
+            tlf.push_str(if code == 'A' as u32 { "▄█@@\n" } else { "$@@\n" });
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("mini.tlf"), stored_zip("mini.tlf", tlf.as_bytes())).unwrap();
+        fs::write(temp_dir.path().join("latin.flf"), b"flf2a$ 1 1 2 -1 1\nby J\xf6rg\n").unwrap();
+
+        let mini = find("mini", temp_dir.path()).unwrap();
+        assert_eq!(mini.text(), Some(tlf.as_str()));
+        let font = FigFont::parse(mini.text().unwrap()).unwrap();
+        assert_eq!(font.render("A A", font.layout()), vec!["▄█ ▄█"]);
+
+        let latin = find("latin", temp_dir.path()).unwrap();
+        assert_eq!(latin.header().unwrap().credit(), Some("by Jörg"));
+        assert!(decode(b"PK\x03\x04 broken").is_err());
+    }
+
+    #[test]
+    fn test_user_fonts_override_builtins() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("small.flf"), "flf2a$ 2 1 4 0 1\nmy small\n").unwrap();
+
+        let fonts = available(temp_dir.path());
+        assert_eq!(fonts.len(), BUILTIN.len());
+        let small = fonts.iter().find(|font| font.name == "small").unwrap();
+        assert_eq!(small.header().unwrap().credit(), Some("my small"));
+
+        let found = find("small", temp_dir.path()).unwrap();
+        assert_eq!(found.source, FontSource::File(temp_dir.path().join("small.flf")));
+        assert_eq!(find("big", temp_dir.path()).unwrap().source, FontSource::Builtin);
+    }
+}
@@ -0,0 +1,408 @@
+//! Real pixel images on terminals that can show them, through the kitty
+//! graphics protocol, iTerm2 inline images or Sixel. The greeting keeps
+//! blank cells where the image goes and the image is drawn over them.
+
+use anyhow::{Context, Result};
+use image::imageops::{self, FilterType};
+use image::{ImageOutputFormat, RgbaImage};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::io::Cursor;
+
+use crate::grid::{Cell, CellGrid};
+
+/// Cell size in pixels assumed when the terminal doesn't report one
+const DEFAULT_CELL: (u32, u32) = (10, 20);
+
+/// Largest payload in one kitty graphics escape
+const KITTY_CHUNK: usize = 4096;
+
+/// How long to wait for the terminal to answer a device attributes query
+const QUERY_TIMEOUT_MS: i32 = 100;
+
+/// Sixel pixels at least this opaque are drawn
+const OPAQUE: u8 = 128;
+
+/// Levels of each channel in the Sixel palette, a 6x6x6 color cube
+const SIXEL_LEVELS: u32 = 6;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Which graphics protocol draws `--image`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImageProtocol {
+    /// The best protocol the terminal supports, or characters when it has none
+    #[default]
+    Auto,
+    Kitty,
+    Iterm2,
+    Sixel,
+    /// Always draw the image with characters, as --image-mode says
+    None,
+}
+
+impl ImageProtocol {
+    /// The protocol to use, detecting the terminal's for `Auto`
+    pub fn resolve(self) -> Option<Protocol> {
+        match self {
+            ImageProtocol::Auto => detect(),
+            ImageProtocol::Kitty => Some(Protocol::Kitty),
+            ImageProtocol::Iterm2 => Some(Protocol::Iterm2),
+            ImageProtocol::Sixel => Some(Protocol::Sixel),
+            ImageProtocol::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+}
+
+/// A pixel image drawn over the cells reserved for it at the top left of
+/// the greeting
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlay {
+    escape: String,
+}
+
+impl Overlay {
+    /// Escapes that draw the image once a greeting `height` rows tall has
+    /// been printed, leaving the cursor where it was, below the greeting
+    pub fn after(&self, height: usize) -> String {
+        format!("\x1b7\x1b[{}A\r{}\x1b8", height, self.escape)
+    }
+}
+
+/// Encode an image for `protocol` at its natural size in cells, at most
+/// `max_width` columns, along with blank cells to reserve for it
+pub fn draw(image: &RgbaImage, protocol: Protocol, max_width: usize) -> Result<(CellGrid, Overlay)> {
+    let (cell_width, cell_height) = cell_size().unwrap_or(DEFAULT_CELL);
+    let columns = image.width().div_ceil(cell_width).min(max_width as u32).max(1);
+    let width = columns * cell_width;
+    let height = (width as f32 * image.height() as f32 / image.width().max(1) as f32)
+        .round()
+        .max(1.0) as u32;
+    let rows = height.div_ceil(cell_height);
+    let scaled = imageops::resize(image, width, height, FilterType::Triangle);
+
+    let escape = match protocol {
+        Protocol::Kitty => kitty(&png(&scaled)?, columns),
+        Protocol::Iterm2 => iterm2(&png(&scaled)?, columns),
+        Protocol::Sixel => sixel(&scaled),
+    };
+
+    let mut reserved = CellGrid::new();
+    for _ in 0..rows {
+        reserved.push_row(vec![Cell::blank(); columns as usize]);
+    }
+    Ok((reserved, Overlay { escape }))
+}
+
+/// Pick a protocol from what the environment says about the terminal,
+/// asking the terminal itself whether it does Sixel as a last resort
+fn detect() -> Option<Protocol> {
+    let env = |name: &str| std::env::var(name).ok();
+    if env("TMUX").is_some() || env("STY").is_some() {
+        // Multiplexers don't pass images through without extra setup
+        return None;
+    }
+    detect_from(env).or_else(|| query_sixel().then_some(Protocol::Sixel))
+}
+
+fn detect_from(env: impl Fn(&str) -> Option<String>) -> Option<Protocol> {
+    let term = env("TERM").unwrap_or_default();
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+
+    if env("KITTY_WINDOW_ID").is_some()
+        || matches!(term.as_str(), "xterm-kitty" | "xterm-ghostty")
+        || program == "ghostty"
+    {
+        Some(Protocol::Kitty)
+    } else if matches!(program.as_str(), "iTerm.app" | "WezTerm")
+        || env("LC_TERMINAL").as_deref() == Some("iTerm2")
+    {
+        Some(Protocol::Iterm2)
+    } else if term.contains("sixel") || matches!(term.as_str(), "foot" | "foot-extra" | "mlterm") {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+/// Whether a primary device attributes reply, such as `ESC [ ? 62 ; 4 c`,
+/// lists Sixel graphics (attribute 4) after the device class
+fn reports_sixel(reply: &str) -> bool {
+    reply
+        .split_once("\x1b[?")
+        .and_then(|(_, rest)| rest.split_once('c'))
+        .is_some_and(|(attributes, _)| attributes.split(';').skip(1).any(|a| a == "4"))
+}
+
+#[cfg(unix)]
+fn query_sixel() -> bool {
+    use std::io::{self, IsTerminal, Write};
+    use std::os::unix::io::AsRawFd;
+
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return false;
+    }
+    if crossterm::terminal::enable_raw_mode().is_err() {
+        return false;
+    }
+
+    let mut reply = Vec::new();
+    let mut stdout = io::stdout();
+    if stdout.write_all(b"\x1b[c").and_then(|_| stdout.flush()).is_ok() {
+        let fd = io::stdin().as_raw_fd();
+        let mut poll = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let mut byte = 0u8;
+        // SAFETY: poll gets one valid pollfd and read a one-byte buffer
+        while unsafe { libc::poll(&mut poll, 1, QUERY_TIMEOUT_MS) } > 0
+            && unsafe { libc::read(fd, (&mut byte as *mut u8).cast(), 1) } == 1
+        {
+            reply.push(byte);
+            if byte == b'c' {
+                break;
+            }
+        }
+    }
+    let _ = crossterm::terminal::disable_raw_mode();
+    reports_sixel(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(not(unix))]
+fn query_sixel() -> bool {
+    false
+}
+
+/// Pixel size of one cell, from the terminal's window size
+#[cfg(unix)]
+fn cell_size() -> Option<(u32, u32)> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // SAFETY: size is a valid out-pointer for TIOCGWINSZ
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 {
+        return None;
+    }
+    per_cell((size.ws_xpixel, size.ws_ypixel), (size.ws_col, size.ws_row))
+}
+
+/// Pixels per cell for a window of `pixels` showing `cells`, or `None` when
+/// either side comes out at zero, as in a window fewer pixels than cells wide
+#[cfg(unix)]
+fn per_cell(pixels: (u16, u16), cells: (u16, u16)) -> Option<(u32, u32)> {
+    let width = pixels.0.checked_div(cells.0)?;
+    let height = pixels.1.checked_div(cells.1)?;
+    (width > 0 && height > 0).then_some((width as u32, height as u32))
+}
+
+#[cfg(not(unix))]
+fn cell_size() -> Option<(u32, u32)> {
+    None
+}
+
+fn png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, ImageOutputFormat::Png)
+        .context("Failed to encode image")?;
+    Ok(bytes.into_inner())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |value, (i, &byte)| value | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// A PNG sent in chunks, scaled to `columns` cells wide. The cursor stays
+/// put and the terminal sends no reply.
+fn kitty(png: &[u8], columns: u32) -> String {
+    let payload = base64(png);
+    let chunks: Vec<&str> = payload
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+
+    let mut escape = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        if index == 0 {
+            let _ = write!(escape, "\x1b_Ga=T,f=100,c={},C=1,q=2,m={};", columns, more);
+        } else {
+            let _ = write!(escape, "\x1b_Gm={};", more);
+        }
+        let _ = write!(escape, "{}\x1b\\", chunk);
+    }
+    escape
+}
+
+fn iterm2(png: &[u8], columns: u32) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        columns,
+        base64(png)
+    )
+}
+
+/// Sixel bands six pixels tall, one pass per palette color in each band,
+/// with transparent pixels left undrawn
+fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let index = |x: u32, y: u32| {
+        let pixel = image.get_pixel(x, y);
+        (pixel[3] >= OPAQUE).then(|| {
+            let level = |channel: u8| (channel as u32 * (SIXEL_LEVELS - 1) + 127) / 255;
+            (level(pixel[0]) * SIXEL_LEVELS + level(pixel[1])) * SIXEL_LEVELS + level(pixel[2])
+        })
+    };
+
+    let mut escape = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    let used: BTreeSet<u32> = (0..height)
+        .flat_map(|y| (0..width).filter_map(move |x| index(x, y)))
+        .collect();
+    for &color in &used {
+        let percent = |level: u32| level * 100 / (SIXEL_LEVELS - 1);
+        let (r, g, b) = (
+            color / (SIXEL_LEVELS * SIXEL_LEVELS),
+            color / SIXEL_LEVELS % SIXEL_LEVELS,
+            color % SIXEL_LEVELS,
+        );
+        let _ = write!(escape, "#{};2;{};{};{}", color, percent(r), percent(g), percent(b));
+    }
+
+    for top in (0..height).step_by(6) {
+        if top > 0 {
+            escape.push('-');
+        }
+        let rows = top..(top + 6).min(height);
+        let colors: BTreeSet<u32> = rows
+            .clone()
+            .flat_map(|y| (0..width).filter_map(move |x| index(x, y)))
+            .collect();
+        for (pass, &color) in colors.iter().enumerate() {
+            if pass > 0 {
+                escape.push('$');
+            }
+            let sixels: Vec<u8> = (0..width)
+                .map(|x| {
+                    let bits = rows
+                        .clone()
+                        .filter(|&y| index(x, y) == Some(color))
+                        .fold(0, |bits, y| bits | 1 << (y - top));
+                    63 + bits
+                })
+                .collect();
+            let _ = write!(escape, "#{}", color);
+            // Empty sixels at the end of a pass don't need drawing
+            let end = sixels.iter().rposition(|&sixel| sixel != b'?').map_or(0, |i| i + 1);
+            push_runs(&mut escape, &sixels[..end]);
+        }
+    }
+    escape.push_str("\x1b\\");
+    escape
+}
+
+/// Sixel characters, with runs of more than three compressed to `!count`
+fn push_runs(escape: &mut String, sixels: &[u8]) {
+    let mut rest = sixels;
+    while let Some(&sixel) = rest.first() {
+        let run = rest.iter().take_while(|&&s| s == sixel).count();
+        if run > 3 {
+            let _ = write!(escape, "!{}{}", run, sixel as char);
+        } else {
+            escape.push_str(&(sixel as char).to_string().repeat(run));
+        }
+        rest = &rest[run..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    #[test]
+    fn test_sixel_bands_and_palette() {
+        // A red pixel beside a transparent one, over a full row of red
+        let image = RgbaImage::from_fn(2, 2, |x, y| if x == 1 && y == 0 { CLEAR } else { RED });
+        assert_eq!(sixel(&image), "\x1bP0;1;0q\"1;1;2;2#180;2;100;0;0#180BA\x1b\\");
+
+        let tall = RgbaImage::from_pixel(5, 7, RED);
+        assert_eq!(
+            sixel(&tall),
+            "\x1bP0;1;0q\"1;1;5;7#180;2;100;0;0#180!5~-#180!5@\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_kitty_and_iterm2_escapes() {
+        assert_eq!(base64(b"greet"), "Z3JlZXQ=");
+        assert_eq!(base64(b"me"), "bWU=");
+
+        assert_eq!(kitty(b"greet", 3), "\x1b_Ga=T,f=100,c=3,C=1,q=2,m=0;Z3JlZXQ=\x1b\\");
+        let long = kitty(&[0; 4000], 3);
+        assert!(long.starts_with("\x1b_Ga=T,f=100,c=3,C=1,q=2,m=1;AAAA"));
+        assert!(long.contains("\x1b\\\x1b_Gm=0;AAAA"));
+        assert!(long.ends_with("AA==\x1b\\"));
+
+        assert_eq!(
+            iterm2(b"greet", 3),
+            "\x1b]1337;File=inline=1;size=5;width=3;preserveAspectRatio=1:Z3JlZXQ=\x07"
+        );
+    }
+
+    #[test]
+    fn test_protocol_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(detect_from(env(&[("TERM", "xterm-kitty")])), Some(Protocol::Kitty));
+        assert_eq!(detect_from(env(&[("TERM_PROGRAM", "iTerm.app")])), Some(Protocol::Iterm2));
+        assert_eq!(detect_from(env(&[("TERM", "foot")])), Some(Protocol::Sixel));
+        assert_eq!(detect_from(env(&[("TERM", "xterm-256color")])), None);
+
+        assert!(reports_sixel("\x1b[?62;4;6;22c"));
+        assert!(!reports_sixel("\x1b[?62;22c"));
+        assert!(!reports_sixel("\x1b[?4;6c"));
+        assert!(!reports_sixel(""));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_cell_size_needs_both_sides() {
+        assert_eq!(per_cell((800, 480), (80, 24)), Some((10, 20)));
+        assert_eq!(per_cell((0, 0), (80, 24)), None);
+        assert_eq!(per_cell((800, 480), (0, 24)), None);
+        // Fewer pixels than rows would make a zero-height cell
+        assert_eq!(per_cell((800, 20), (80, 24)), None);
+        assert_eq!(per_cell((40, 480), (80, 24)), None);
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, NaiveDate};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// A stored greeting with optional per-greeting theme and font overrides
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Greeting {
+    pub name: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// How `greetme -r` picks from the stored greetings
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationMode {
+    #[default]
+    Sequential,
+    Random,
+    Daily,
+    Weighted,
+}
+
+/// Rotation state persisted between runs so sequential rotation survives
+/// across shells
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RotationState {
+    #[serde(default)]
+    pub next_index: usize,
+}
+
+impl RotationState {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).context("Failed to read rotation state")?;
+        toml::from_str(&content).context("Failed to parse rotation state")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self).context("Failed to serialize rotation state")?;
+        fs::write(path, content).context("Failed to write rotation state")?;
+        Ok(())
+    }
+}
+
+/// Pick the index of the greeting to show next, advancing `state` for
+/// sequential rotation. Returns `None` when there is nothing to pick from.
+pub fn select<R: Rng>(
+    greetings: &[Greeting],
+    mode: RotationMode,
+    state: &mut RotationState,
+    today: NaiveDate,
+    rng: &mut R,
+) -> Option<usize> {
+    if greetings.is_empty() {
+        return None;
+    }
+
+    let index = match mode {
+        RotationMode::Sequential => {
+            let index = state.next_index % greetings.len();
+            state.next_index = (index + 1) % greetings.len();
+            index
+        }
+        RotationMode::Random => rng.gen_range(0..greetings.len()),
+        RotationMode::Daily => today.num_days_from_ce().unsigned_abs() as usize % greetings.len(),
+        RotationMode::Weighted => {
+            let weights = greetings.iter().map(|g| g.weight);
+            match WeightedIndex::new(weights) {
+                Ok(dist) => dist.sample(rng),
+                // All weights are zero; treat every greeting equally
+                Err(_) => rng.gen_range(0..greetings.len()),
+            }
+        }
+    };
+
+    Some(index)
+}
+
+/// Add a greeting, rejecting duplicate names
+pub fn add(greetings: &mut Vec<Greeting>, greeting: Greeting) -> Result<()> {
+    if greeting.name.trim().is_empty() {
+        return Err(anyhow!("Greeting name cannot be empty"));
+    }
+    if greetings.iter().any(|g| g.name == greeting.name) {
+        return Err(anyhow!(
+            "A greeting named '{}' already exists. Remove it first to replace it.",
+            greeting.name
+        ));
+    }
+    greetings.push(greeting);
+    Ok(())
+}
+
+/// Remove a greeting by name, returning it
+pub fn remove(greetings: &mut Vec<Greeting>, name: &str) -> Result<Greeting> {
+    let index = greetings
+        .iter()
+        .position(|g| g.name == name)
+        .ok_or_else(|| anyhow!("No greeting named '{}'", name))?;
+    Ok(greetings.remove(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn greeting(name: &str, weight: u32) -> Greeting {
+        Greeting {
+            name: name.to_string(),
+            text: format!("Hello from {}", name),
+            theme: None,
+            font: None,
+            weight,
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_sequential_wraps_around() {
+        let greetings = vec![greeting("a", 1), greeting("b", 1), greeting("c", 1)];
+        let mut state = RotationState::default();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let picks: Vec<usize> = (0..4)
+            .map(|_| select(&greetings, RotationMode::Sequential, &mut state, day(1), &mut rng))
+            .map(Option::unwrap)
+            .collect();
+        assert_eq!(picks, vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_daily_is_stable_within_a_day() {
+        let greetings = vec![greeting("a", 1), greeting("b", 1), greeting("c", 1)];
+        let mut state = RotationState::default();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let first = select(&greetings, RotationMode::Daily, &mut state, day(5), &mut rng);
+        let second = select(&greetings, RotationMode::Daily, &mut state, day(5), &mut rng);
+        let next_day = select(&greetings, RotationMode::Daily, &mut state, day(6), &mut rng);
+        assert_eq!(first, second);
+        assert_ne!(first, next_day);
+    }
+
+    #[test]
+    fn test_weighted_skips_zero_weight() {
+        let greetings = vec![greeting("never", 0), greeting("always", 3)];
+        let mut state = RotationState::default();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..50 {
+            let pick = select(&greetings, RotationMode::Weighted, &mut state, day(1), &mut rng);
+            assert_eq!(pick, Some(1));
+        }
+    }
+
+    #[test]
+    fn test_add_rejects_duplicates() {
+        let mut greetings = vec![greeting("a", 1)];
+        assert!(add(&mut greetings, greeting("a", 1)).is_err());
+        assert!(add(&mut greetings, greeting("b", 1)).is_ok());
+        assert_eq!(remove(&mut greetings, "a").unwrap().name, "a");
+        assert!(remove(&mut greetings, "a").is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use colored::Colorize;
+
+use crate::width;
+
+/// A 24-bit color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const WHITE: Rgb = Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Visual attributes of a single cell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CellStyle {
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl CellStyle {
+    pub fn fg(color: Rgb) -> Self {
+        Self {
+            fg: Some(color),
+            ..Self::default()
+        }
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+}
+
+/// One column of rendered output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    /// Grapheme cluster drawn here. Empty in the second column of a wide
+    /// cluster, which the cluster before it covers.
+    pub symbol: String,
+    pub style: CellStyle,
+}
+
+impl Cell {
+    pub fn new(ch: char, style: CellStyle) -> Self {
+        Self::grapheme(&ch.to_string(), style)
+    }
+
+    pub fn grapheme(symbol: &str, style: CellStyle) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            style,
+        }
+    }
+
+    pub fn blank() -> Self {
+        Self::new(' ', CellStyle::default())
+    }
+
+    /// The column a wide cluster spills into
+    pub fn continuation(style: CellStyle) -> Self {
+        Self::grapheme("", style)
+    }
+
+    pub fn is_continuation(&self) -> bool {
+        self.symbol.is_empty()
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.symbol == " "
+    }
+
+    /// Columns the cell's cluster covers, counting its continuation
+    pub fn width(&self) -> usize {
+        width::grapheme_width(&self.symbol)
+    }
+
+    /// Base character of the cluster, for outputs that draw one character
+    /// per column
+    pub fn base(&self) -> char {
+        self.symbol.chars().next().unwrap_or(' ')
+    }
+
+    /// Cells for a line of text, a wide cluster followed by its continuation
+    pub fn line(text: &str, style: CellStyle) -> Vec<Cell> {
+        let mut cells = Vec::new();
+        for (grapheme, columns) in width::graphemes(text) {
+            cells.push(Cell::grapheme(grapheme, style));
+            if columns > 1 {
+                cells.push(Cell::continuation(style));
+            }
+        }
+        cells
+    }
+}
+
+/// Styled character cells produced by the renderer. Every output backend,
+/// from the terminal to file exports, works from this model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CellGrid {
+    rows: Vec<Vec<Cell>>,
+    /// Canvas color behind every cell without its own background
+    pub background: Option<Rgb>,
+}
+
+impl CellGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a grid from lines of text, all in one style
+    pub fn from_lines<S: AsRef<str>>(lines: &[S], style: CellStyle) -> Self {
+        let mut grid = Self::new();
+        for line in lines {
+            grid.push_text(line.as_ref(), style);
+        }
+        grid
+    }
+
+    pub fn push_row(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    /// Append a row of text in one style
+    pub fn push_text(&mut self, text: &str, style: CellStyle) {
+        self.rows.push(Cell::line(text, style));
+    }
+
+    pub fn rows(&self) -> &[Vec<Cell>] {
+        &self.rows
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Append another grid's rows below this one
+    pub fn append_below(&mut self, other: CellGrid) {
+        self.rows.extend(other.rows);
+    }
+
+    /// Place another grid to the right of this one, `gap` columns apart
+    pub fn place_beside(&mut self, other: CellGrid, gap: usize) {
+        let offset = self.width() + gap;
+        let height = self.height().max(other.height());
+        self.rows.resize_with(height, Vec::new);
+
+        for (row, other_row) in self.rows.iter_mut().zip(other.rows) {
+            row.resize(offset, Cell::blank());
+            row.extend(other_row);
+        }
+    }
+
+    /// Build a grid of the same shape and background by transforming every
+    /// cell, given its row and column
+    pub fn map<F: FnMut(usize, usize, &Cell) -> Cell>(&self, mut f: F) -> CellGrid {
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(y, row)| row.iter().enumerate().map(|(x, cell)| f(y, x, cell)).collect())
+            .collect();
+        CellGrid {
+            rows,
+            background: self.background,
+        }
+    }
+
+    /// Split each row into runs of cells that share a style
+    pub fn runs(row: &[Cell]) -> Vec<(CellStyle, String)> {
+        let mut runs: Vec<(CellStyle, String)> = Vec::new();
+        for cell in row {
+            match runs.last_mut() {
+                Some((style, text)) if *style == cell.style => text.push_str(&cell.symbol),
+                _ => runs.push((cell.style, cell.symbol.clone())),
+            }
+        }
+        runs
+    }
+
+    /// Format each row for the terminal. Styling goes through `colored` so
+    /// NO_COLOR and friends are honoured.
+    pub fn to_ansi_lines(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|row| {
+                Self::runs(row)
+                    .into_iter()
+                    .map(|(style, text)| style_run(&text, &style))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn style_run(text: &str, style: &CellStyle) -> String {
+    let mut styled = text.normal();
+    if let Some(fg) = style.fg {
+        styled = styled.truecolor(fg.r, fg.g, fg.b);
+    }
+    if let Some(bg) = style.bg {
+        styled = styled.on_truecolor(bg.r, bg.g, bg.b);
+    }
+    if style.bold {
+        styled = styled.bold();
+    }
+    if style.italic {
+        styled = styled.italic();
+    }
+    styled.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb_to_hex() {
+        assert_eq!(Rgb::new(0x1a, 0x2b, 0x3c).to_hex(), "#1a2b3c");
+    }
+
+    #[test]
+    fn test_place_beside_pads_rows() {
+        let mut left = CellGrid::from_lines(&["ab", "a"], CellStyle::default());
+        let right = CellGrid::from_lines(&["x", "y", "z"], CellStyle::default());
+        left.place_beside(right, 1);
+
+        let text: Vec<String> = left
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|c| c.symbol.as_str()).collect())
+            .collect();
+        assert_eq!(text, vec!["ab x", "a  y", "   z"]);
+    }
+
+    #[test]
+    fn test_runs_merge_identical_styles() {
+        let bold = CellStyle::fg(Rgb::WHITE).bold();
+        let mut row: Vec<Cell> = "ab".chars().map(|c| Cell::new(c, bold)).collect();
+        row.push(Cell::new('c', CellStyle::default()));
+
+        let runs = CellGrid::runs(&row);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0], (bold, "ab".to_string()));
+    }
+
+    #[test]
+    fn test_wide_clusters_take_two_columns() {
+        let mut left = CellGrid::from_lines(&["日e\u{301}", "ab"], CellStyle::default());
+        assert_eq!(left.width(), 3);
+        assert!(left.rows()[0][1].is_continuation());
+        assert_eq!(left.rows()[0][2].symbol, "e\u{301}");
+
+        left.place_beside(CellGrid::from_lines(&["|", "|"], CellStyle::default()), 0);
+        let runs: Vec<String> = left
+            .rows()
+            .iter()
+            .map(|row| CellGrid::runs(row).remove(0).1)
+            .collect();
+        assert_eq!(runs, vec!["日e\u{301}|", "ab |"]);
+    }
+}
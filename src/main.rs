@@ -1,7 +1,23 @@
+mod animation;
+mod backend;
+mod bitmap;
 mod cli;
 mod config;
+mod effects;
+mod export;
+mod fallback;
+mod figlet;
+mod fonts;
+mod graphics;
+mod greetings;
+mod grid;
+mod motd;
+mod picture;
+mod quotes;
 mod render;
+mod sysinfo;
 mod themes;
+mod width;
 
 use anyhow::Result;
 use clap::Parser;
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+/// Where `greetme motd` writes when no target is given
+pub const DEFAULT_MOTD_PATH: &str = "/etc/motd";
+
+/// Where `greetme motd --issue` writes when no target is given
+pub const DEFAULT_ISSUE_PATH: &str = "/etc/issue";
+
+/// Whether a target is a getty issue file, whose backslashes are escapes
+pub fn is_issue_path(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with("issue"))
+        .unwrap_or(false)
+}
+
+/// Escape backslashes so agetty prints the art literally instead of reading
+/// `\ ` or `\n` in FIGlet output as issue escapes
+pub fn escape_issue(text: &str) -> String {
+    text.replace('\\', "\\\\")
+}
+
+/// Assemble the banner file: the rendered greeting followed by footer lines.
+/// Footers are copied verbatim so issue escapes such as `\n` (hostname) and
+/// `\l` (tty) reach getty untouched.
+pub fn banner(rendered: &str, footers: &[String], issue: bool) -> String {
+    let mut content = if issue {
+        escape_issue(rendered)
+    } else {
+        rendered.to_string()
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for footer in footers {
+        content.push_str(footer);
+        content.push('\n');
+    }
+    content
+}
+
+/// Line diff between the current and new banner, marking removed lines with
+/// `-`, added lines with `+` and unchanged lines with a space
+pub fn diff(old: &str, new: &str, path: &Path) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new_lines.len() + 1]; old_lines.len() + 1];
+    for i in (0..old_lines.len()).rev() {
+        for j in (0..new_lines.len()).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut output = format!("--- {}\n+++ {} (new)\n", path.display(), path.display());
+    let (mut i, mut j) = (0, 0);
+    while i < old_lines.len() || j < new_lines.len() {
+        if i < old_lines.len() && j < new_lines.len() && old_lines[i] == new_lines[j] {
+            output.push_str(&format!(" {}\n", old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_lines.len() && (j == new_lines.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            output.push_str(&format!("-{}\n", old_lines[i]));
+            i += 1;
+        } else {
+            output.push_str(&format!("+{}\n", new_lines[j]));
+            j += 1;
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_banner_escapes_art_but_not_footers() {
+        let footers = vec!["\\n on \\l".to_string()];
+        let content = banner("/ \\\n", &footers, true);
+        assert_eq!(content, "/ \\\\\n\\n on \\l\n");
+
+        assert_eq!(banner("/ \\", &footers, false), "/ \\\n\\n on \\l\n");
+    }
+
+    #[test]
+    fn test_issue_path_detection() {
+        assert!(is_issue_path(Path::new("/etc/issue")));
+        assert!(is_issue_path(Path::new("/etc/issue.net")));
+        assert!(!is_issue_path(Path::new("/etc/motd")));
+    }
+
+    #[test]
+    fn test_diff_marks_changed_lines() {
+        let output = diff("a\nb\nc\n", "a\nx\nc\n", Path::new("/etc/motd"));
+        assert_eq!(
+            output,
+            "--- /etc/motd\n+++ /etc/motd (new)\n a\n-b\n+x\n c\n"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Images such as logos drawn as terminal art: truecolor half blocks, an
+//! ASCII ramp or braille dots.
+
+use anyhow::{Context, Result};
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+use crate::bitmap;
+use crate::grid::{Cell, CellGrid, CellStyle, Rgb};
+
+/// Terminal cells are about twice as tall as they are wide
+const CELL_ASPECT: f32 = 2.0;
+
+/// Characters from empty to full ink, for the ASCII mode
+const ASCII_RAMP: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// 4x4 Bayer matrix of thresholds for ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Pixels at least this opaque are drawn
+const OPAQUE: u8 = 128;
+
+/// How image pixels become cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImageMode {
+    /// Two truecolor pixels per cell in half blocks
+    #[default]
+    Blocks,
+    /// One pixel per cell as a character of matching brightness and color
+    Ascii,
+    /// Eight pixels per cell as braille dots, lit where the image is bright
+    Braille,
+}
+
+impl ImageMode {
+    /// Pixel columns and rows each cell samples
+    fn cell_pixels(self) -> (usize, usize) {
+        match self {
+            ImageMode::Blocks => (1, 2),
+            ImageMode::Ascii => (1, 1),
+            ImageMode::Braille => (2, 4),
+        }
+    }
+}
+
+/// How brightness is spread over the few levels the ASCII and braille
+/// modes can show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Dither {
+    /// Round every pixel to the nearest level
+    #[default]
+    None,
+    /// Diffuse each pixel's rounding error to its neighbours
+    FloydSteinberg,
+    /// Offset pixels by a repeating threshold pattern
+    Ordered,
+}
+
+/// Where the image goes relative to the greeting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImagePosition {
+    #[default]
+    Above,
+    Beside,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImageOptions {
+    pub mode: ImageMode,
+    pub dither: Dither,
+    /// Most columns the image may take; smaller images keep their size
+    pub max_width: usize,
+}
+
+/// Read an image file in any format the `image` crate knows
+pub fn load(path: &Path) -> Result<RgbaImage> {
+    let image = image::open(path).context(format!("Failed to load image {}", path.display()))?;
+    Ok(image.to_rgba8())
+}
+
+/// Draw an image as cells, keeping its aspect ratio on the terminal's
+/// tall cells and fitting it to the maximum width
+pub fn to_grid(image: &RgbaImage, options: &ImageOptions) -> CellGrid {
+    let (cell_width, cell_height) = options.mode.cell_pixels();
+    let columns = (image.width() as usize)
+        .div_ceil(cell_width)
+        .min(options.max_width)
+        .max(1);
+    let ratio = image.height() as f32 / image.width().max(1) as f32;
+    let rows = (columns as f32 * ratio / CELL_ASPECT).round().max(1.0) as usize;
+
+    let sample = imageops::resize(
+        image,
+        (columns * cell_width) as u32,
+        (rows * cell_height) as u32,
+        FilterType::Triangle,
+    );
+    match options.mode {
+        ImageMode::Blocks => blocks(&sample),
+        ImageMode::Ascii => ascii(&sample, options.dither),
+        ImageMode::Braille => braille(&sample, options.dither),
+    }
+}
+
+fn color(pixel: &Rgba<u8>) -> Rgb {
+    Rgb::new(pixel[0], pixel[1], pixel[2])
+}
+
+fn opaque(pixel: &Rgba<u8>) -> bool {
+    pixel[3] >= OPAQUE
+}
+
+/// Upper pixel in the foreground of '▀', lower pixel in the background
+fn blocks(sample: &RgbaImage) -> CellGrid {
+    let mut grid = CellGrid::new();
+    for y in (0..sample.height()).step_by(2) {
+        let row = (0..sample.width())
+            .map(|x| {
+                let top = sample.get_pixel(x, y);
+                let bottom = sample.get_pixel(x, y + 1);
+                match (opaque(top), opaque(bottom)) {
+                    (false, false) => Cell::blank(),
+                    (true, false) => Cell::new('▀', CellStyle::fg(color(top))),
+                    (false, true) => Cell::new('▄', CellStyle::fg(color(bottom))),
+                    (true, true) => Cell::new(
+                        '▀',
+                        CellStyle {
+                            bg: Some(color(bottom)),
+                            ..CellStyle::fg(color(top))
+                        },
+                    ),
+                }
+            })
+            .collect();
+        grid.push_row(row);
+    }
+    grid
+}
+
+fn ascii(sample: &RgbaImage, dither: Dither) -> CellGrid {
+    let levels = quantize(sample, ASCII_RAMP.len(), dither);
+    let mut grid = CellGrid::new();
+    for (y, row) in levels.iter().enumerate() {
+        let cells = row
+            .iter()
+            .enumerate()
+            .map(|(x, &level)| {
+                let pixel = sample.get_pixel(x as u32, y as u32);
+                match level {
+                    0 => Cell::blank(),
+                    _ if !opaque(pixel) => Cell::blank(),
+                    _ => Cell::new(ASCII_RAMP[level], CellStyle::fg(color(pixel))),
+                }
+            })
+            .collect();
+        grid.push_row(cells);
+    }
+    grid
+}
+
+/// Dots where the image is bright, in the average color of the lit pixels
+fn braille(sample: &RgbaImage, dither: Dither) -> CellGrid {
+    let levels = quantize(sample, 2, dither);
+    let lit = |x: usize, y: usize| {
+        levels[y][x] == 1 && opaque(sample.get_pixel(x as u32, y as u32))
+    };
+
+    let mut grid = CellGrid::new();
+    for y in (0..levels.len()).step_by(4) {
+        let row = (0..sample.width() as usize)
+            .step_by(2)
+            .map(|x| {
+                let symbol = bitmap::braille(|dx, dy| lit(x + dx, y + dy));
+                let dots: Vec<Rgb> = (0..2)
+                    .flat_map(|dx| (0..4).map(move |dy| (x + dx, y + dy)))
+                    .filter(|&(x, y)| lit(x, y))
+                    .map(|(x, y)| color(sample.get_pixel(x as u32, y as u32)))
+                    .collect();
+                match average(&dots) {
+                    Some(average) => Cell::new(symbol, CellStyle::fg(average)),
+                    None => Cell::blank(),
+                }
+            })
+            .collect();
+        grid.push_row(row);
+    }
+    grid
+}
+
+fn average(colors: &[Rgb]) -> Option<Rgb> {
+    if colors.is_empty() {
+        return None;
+    }
+    let channel = |pick: fn(&Rgb) -> u8| {
+        (colors.iter().map(|c| pick(c) as usize).sum::<usize>() / colors.len()) as u8
+    };
+    Some(Rgb::new(channel(|c| c.r), channel(|c| c.g), channel(|c| c.b)))
+}
+
+/// Brightness of every pixel rounded to one of `levels`, from 0 for dark
+/// or transparent pixels up to `levels - 1`
+fn quantize(sample: &RgbaImage, levels: usize, dither: Dither) -> Vec<Vec<usize>> {
+    let (width, height) = (sample.width() as usize, sample.height() as usize);
+    let mut brightness: Vec<Vec<f32>> = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let pixel = sample.get_pixel(x as u32, y as u32);
+                    let luma = 0.2126 * pixel[0] as f32
+                        + 0.7152 * pixel[1] as f32
+                        + 0.0722 * pixel[2] as f32;
+                    luma / 255.0 * pixel[3] as f32 / 255.0
+                })
+                .collect()
+        })
+        .collect();
+
+    let steps = (levels - 1) as f32;
+    let mut quantized = vec![vec![0; width]; height];
+    for y in 0..height {
+        for x in 0..width {
+            let mut value = brightness[y][x];
+            if dither == Dither::Ordered {
+                value += ((BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5) / steps;
+            }
+            let level = (value * steps).round().clamp(0.0, steps);
+            quantized[y][x] = level as usize;
+
+            if dither == Dither::FloydSteinberg {
+                let error = brightness[y][x] - level / steps;
+                let mut spread = |dx: isize, dy: usize, share: f32| {
+                    let nx = x as isize + dx;
+                    if nx >= 0 && (nx as usize) < width && y + dy < height {
+                        brightness[y + dy][nx as usize] += error * share;
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+    quantized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+    const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    fn options(mode: ImageMode, dither: Dither, max_width: usize) -> ImageOptions {
+        ImageOptions {
+            mode,
+            dither,
+            max_width,
+        }
+    }
+
+    fn text(grid: &CellGrid) -> Vec<String> {
+        grid.rows()
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.symbol.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_half_blocks_pair_pixels_and_skip_transparency() {
+        // Columns: red over blue, red over nothing, nothing over blue, nothing
+        let image = RgbaImage::from_fn(4, 2, |x, y| match (x, y) {
+            (0, 0) | (1, 0) => RED,
+            (0, 1) | (2, 1) => BLUE,
+            _ => CLEAR,
+        });
+        let grid = blocks(&image);
+        assert_eq!(text(&grid), vec!["▀▀▄ "]);
+
+        let cells = &grid.rows()[0];
+        assert_eq!(cells[0].style.fg, Some(Rgb::new(255, 0, 0)));
+        assert_eq!(cells[0].style.bg, Some(Rgb::new(0, 0, 255)));
+        assert_eq!(cells[1].style.bg, None);
+        assert_eq!(cells[2].style.fg, Some(Rgb::new(0, 0, 255)));
+    }
+
+    #[test]
+    fn test_images_keep_their_aspect_and_fit_the_width() {
+        let square = RgbaImage::from_pixel(100, 100, RED);
+        let grid = to_grid(&square, &options(ImageMode::Blocks, Dither::None, 40));
+        assert_eq!((grid.width(), grid.height()), (40, 20));
+
+        // Small images aren't enlarged
+        let small = RgbaImage::from_pixel(8, 8, Rgba([255, 255, 255, 255]));
+        let grid = to_grid(&small, &options(ImageMode::Braille, Dither::None, 40));
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+        assert!(text(&grid).iter().all(|row| row == "⣿⣿⣿⣿"));
+    }
+
+    #[test]
+    fn test_dithering_spreads_mid_grey() {
+        let grey = RgbaImage::from_pixel(8, 8, Rgba([128, 128, 128, 255]));
+        let lit = |dither| -> usize {
+            quantize(&grey, 2, dither).iter().flatten().sum()
+        };
+        // Rounding alone lights every pixel; dithering lights about half
+        assert_eq!(lit(Dither::None), 64);
+        assert_eq!(lit(Dither::Ordered), 32);
+        assert!((28..=36).contains(&lit(Dither::FloydSteinberg)));
+
+        let ramp = RgbaImage::from_fn(10, 1, |x, _| {
+            let level = (x * 255 / 9) as u8;
+            Rgba([level, level, level, 255])
+        });
+        let grid = to_grid(&ramp, &options(ImageMode::Ascii, Dither::None, 10));
+        assert_eq!(text(&grid)[0].trim_start(), ".:-=+*#%@");
+    }
+}
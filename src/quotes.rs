@@ -0,0 +1,195 @@
+use anyhow::{anyhow, Context, Result};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::width;
+
+/// Locations of the system fortune database, checked in order
+const SYSTEM_FORTUNE_DIRS: &[&str] = &[
+    "/usr/share/games/fortunes",
+    "/usr/share/fortune",
+    "/usr/share/fortunes",
+];
+
+/// Constraints a quote must satisfy to be picked
+#[derive(Debug, Default)]
+pub struct QuoteFilter {
+    pub max_length: Option<usize>,
+    pub pattern: Option<Regex>,
+}
+
+impl QuoteFilter {
+    pub fn matches(&self, quote: &str) -> bool {
+        if let Some(max) = self.max_length {
+            if quote.chars().count() > max {
+                return false;
+            }
+        }
+        match &self.pattern {
+            Some(pattern) => pattern.is_match(quote),
+            None => true,
+        }
+    }
+}
+
+/// Return the system fortune database directory if one is installed
+pub fn system_source() -> Option<PathBuf> {
+    SYSTEM_FORTUNE_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_dir())
+}
+
+/// Split fortune-format content into entries. Entries are separated by lines
+/// containing a single `%`.
+pub fn parse_fortunes(content: &str) -> Vec<String> {
+    let mut quotes = Vec::new();
+    let mut current = Vec::new();
+
+    for line in content.lines() {
+        if line.trim_end() == "%" {
+            push_entry(&mut quotes, &current);
+            current.clear();
+        } else {
+            current.push(line);
+        }
+    }
+    push_entry(&mut quotes, &current);
+
+    quotes
+}
+
+fn push_entry(quotes: &mut Vec<String>, lines: &[&str]) {
+    let entry = lines.join("\n");
+    let entry = entry.trim();
+    if !entry.is_empty() {
+        quotes.push(entry.to_string());
+    }
+}
+
+/// Load every entry from a fortune file, or from all fortune files in a
+/// directory. The `.dat` index files `strfile` writes alongside are skipped.
+pub fn load_quotes(path: &Path) -> Result<Vec<String>> {
+    if path.is_dir() {
+        let mut files = Vec::new();
+        let entries = fs::read_dir(path)
+            .context(format!("Failed to read quote directory: {}", path.display()))?;
+        for entry in entries {
+            let file = entry?.path();
+            let is_index = file.extension().is_some_and(|ext| ext == "dat");
+            if file.is_file() && !is_index {
+                files.push(file);
+            }
+        }
+        files.sort();
+
+        let mut quotes = Vec::new();
+        for file in files {
+            // Fortune directories can contain binary or non-UTF-8 files
+            if let Ok(content) = fs::read_to_string(&file) {
+                quotes.extend(parse_fortunes(&content));
+            }
+        }
+        Ok(quotes)
+    } else {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read quote file: {}", path.display()))?;
+        Ok(parse_fortunes(&content))
+    }
+}
+
+/// Pick a random quote that satisfies `filter`
+pub fn pick<R: Rng>(quotes: &[String], filter: &QuoteFilter, rng: &mut R) -> Result<String> {
+    let candidates: Vec<&String> = quotes.iter().filter(|q| filter.matches(q)).collect();
+    candidates
+        .choose(rng)
+        .map(|q| q.to_string())
+        .ok_or_else(|| anyhow!("No quotes match the given filters"))
+}
+
+/// Word-wrap text to `width` columns, keeping the source line breaks.
+/// Words longer than the width are placed on a line of their own.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && width::width(&line) + 1 + width::width(word) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_fortunes() {
+        let content = "First quote\n%\nSecond quote\n  -- Someone\n%\n%\n";
+        let quotes = parse_fortunes(content);
+        assert_eq!(quotes, vec!["First quote", "Second quote\n  -- Someone"]);
+    }
+
+    #[test]
+    fn test_filter_and_pick() {
+        let quotes = vec![
+            "short".to_string(),
+            "a much longer quote about rust".to_string(),
+            "another long one".to_string(),
+        ];
+        let filter = QuoteFilter {
+            max_length: Some(20),
+            pattern: Some(Regex::new("long").unwrap()),
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(pick(&quotes, &filter, &mut rng).unwrap(), "another long one");
+
+        let filter = QuoteFilter {
+            max_length: Some(2),
+            pattern: None,
+        };
+        assert!(pick(&quotes, &filter, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_load_directory_skips_dat_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("wisdom"), "one\n%\ntwo\n").unwrap();
+        fs::write(temp_dir.path().join("wisdom.dat"), "three\n%\n").unwrap();
+
+        let quotes = load_quotes(temp_dir.path()).unwrap();
+        assert_eq!(quotes, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn test_wrap() {
+        let lines = wrap("the quick brown fox jumps", 10);
+        assert_eq!(lines, vec!["the quick", "brown fox", "jumps"]);
+    }
+
+    #[test]
+    fn test_wrap_counts_display_columns() {
+        // Each ideograph is two columns wide; accents add none
+        let lines = wrap("日本語 です cafe\u{301} ok", 8);
+        assert_eq!(lines, vec!["日本語", "です", "cafe\u{301} ok"]);
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(wrap(&format!("{0} {0} {0}", family), 5), vec![format!("{0} {0}", family), family.to_string()]);
+    }
+}
@@ -1,132 +1,263 @@
-use anyhow::Result;
-use colored::Colorize;
-use figlet_rs::FIGfont;
+use terminal_size::{terminal_size, Height, Width};
 
+use crate::backend::FontBackend;
+use crate::grid::{Cell, CellGrid, CellStyle};
+use crate::picture::ImagePosition;
+use crate::quotes;
+use crate::sysinfo::{InfoLine, PanelLayout};
 use crate::themes::Theme;
 
+/// Width used for wrapping when the terminal size can't be determined
+const DEFAULT_WIDTH: usize = 80;
+
+/// Columns between the art and the info panel
+const PANEL_GAP: usize = 3;
+
+/// Columns of the terminal, or a default when stdout isn't one
+pub fn terminal_width() -> usize {
+    terminal_size()
+        .map(|(Width(w), _)| w as usize)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Rows of the terminal, if stdout is one
+pub fn terminal_height() -> Option<usize> {
+    terminal_size().map(|(_, Height(h))| h as usize)
+}
+
 pub struct Renderer {
     theme: Theme,
-    font_name: String,
+    font: Box<dyn FontBackend>,
     size: f32,
 }
 
 impl Renderer {
-    pub fn new(theme: Theme, font_name: &str, size: f32) -> Self {
-        Self {
-            theme,
-            font_name: font_name.to_string(),
-            size,
+    pub fn new(theme: Theme, font: Box<dyn FontBackend>, size: f32) -> Self {
+        Self { theme, font, size }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn font_name(&self) -> &str {
+        self.font.name()
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn render(&self, text: &str) {
+        self.print(&self.art_grid(text));
+    }
+
+    /// Print a composed grid to stdout
+    pub fn print(&self, grid: &CellGrid) {
+        for line in grid.to_ansi_lines() {
+            println!("{}", line);
         }
     }
 
-    pub fn render(&self, text: &str) -> Result<()> {
-        let output = self.render_figlet(text)?;
-        
-        // Apply size scaling if needed
-        let scaled_output = if self.size != 1.0 {
-            self.scale_text(&output, self.size)
-        } else {
-            output
-        };
-        
-        // Apply colors based on theme
-        let colored_output = match self.theme.ascii_art_style.as_str() {
-            "bold" => scaled_output.color(self.theme.get_strong_color()).bold().to_string(),
-            "italic" => scaled_output.color(self.theme.get_accent_color()).italic().to_string(),
-            "normal" | _ => scaled_output.color(self.theme.get_foreground_color()).to_string(),
+    /// Render the art into styled cells on the theme background, scaled to
+    /// the configured size
+    pub fn art_grid(&self, text: &str) -> CellGrid {
+        let style = self.art_style();
+        let mut grid = self.scale(self.font.render(text)).map(|_, _, cell| {
+            if cell.style == CellStyle::default() {
+                Cell::grapheme(&cell.symbol, style)
+            } else {
+                cell.clone()
+            }
+        });
+        grid = self.theme.fill.apply(&grid, &self.theme);
+        grid.background = Some(self.theme.get_background_color());
+        grid
+    }
+
+    /// Attach a system information panel beside the art, or below it when
+    /// the terminal is too narrow or the layout asks for it
+    pub fn add_panel(&self, grid: &mut CellGrid, info: &[InfoLine], layout: PanelLayout) {
+        let panel = self.info_grid(info);
+        let side_by_side = match layout {
+            PanelLayout::Side => true,
+            PanelLayout::Below => false,
+            PanelLayout::Auto => grid.width() + PANEL_GAP + panel.width() <= terminal_width(),
         };
 
-        println!("{}", colored_output);
-        Ok(())
+        if side_by_side {
+            grid.place_beside(panel, PANEL_GAP);
+        } else {
+            grid.append_below(panel);
+        }
+    }
+
+    /// Put an image above the art or beside it, keeping the theme
+    /// background
+    pub fn add_image(&self, grid: &mut CellGrid, image: CellGrid, position: ImagePosition) {
+        let background = grid.background;
+        let art = std::mem::replace(grid, image);
+        match position {
+            ImagePosition::Above => grid.append_below(art),
+            ImagePosition::Beside => grid.place_beside(art, PANEL_GAP),
+        }
+        grid.background = background;
+    }
+
+    /// Add a word-wrapped subtitle beneath the headline in the theme's dim
+    /// color
+    pub fn add_subtitle(&self, grid: &mut CellGrid, text: &str) {
+        let lines = quotes::wrap(text, terminal_width());
+        grid.append_below(CellGrid::from_lines(&lines, CellStyle::fg(self.theme.get_dim_color())));
     }
 
-    fn scale_text(&self, text: &str, scale: f32) -> String {
-        if scale <= 1.0 {
-            return text.to_string();
+    /// Theme style for the art
+    fn art_style(&self) -> CellStyle {
+        match self.theme.ascii_art_style.as_str() {
+            "bold" => CellStyle::fg(self.theme.get_strong_color()).bold(),
+            "italic" => CellStyle::fg(self.theme.get_accent_color()).italic(),
+            _ => CellStyle::fg(self.theme.get_foreground_color()),
         }
-        
-        let lines: Vec<&str> = text.lines().collect();
-        let mut scaled_lines = Vec::new();
-        
-        // Vertical scaling - repeat each line
-        let vertical_repeat = scale.round() as usize;
-        for line in lines {
-            // Horizontal scaling - repeat each character
-            let scaled_line = if scale >= 2.0 {
-                line.chars()
-                    .map(|c| c.to_string().repeat(scale.round() as usize))
-                    .collect::<String>()
-            } else {
-                line.to_string()
-            };
-            
-            // Repeat line vertically
-            for _ in 0..vertical_repeat {
-                scaled_lines.push(scaled_line.clone());
+    }
+
+    fn info_grid(&self, info: &[InfoLine]) -> CellGrid {
+        let accent = CellStyle::fg(self.theme.get_accent_color()).bold();
+        let foreground = CellStyle::fg(self.theme.get_foreground_color());
+
+        let mut grid = CellGrid::new();
+        for line in info {
+            match line.label {
+                Some(label) => {
+                    let mut row = Cell::line(&format!("{}: ", label), accent);
+                    row.extend(Cell::line(&line.value, foreground));
+                    grid.push_row(row);
+                }
+                None => grid.push_text(&line.value, accent),
             }
         }
-        
-        scaled_lines.join("\n")
-    }
-
-    fn render_figlet(&self, text: &str) -> Result<String> {
-        // Try to load the specified font, fall back to standard
-        let font_result = match self.font_name.as_str() {
-            "standard" => FIGfont::standard(),
-            "small" => FIGfont::from_content(include_str!("../fonts/small.flf")),
-            "big" => FIGfont::from_content(include_str!("../fonts/big.flf")),
-            "banner" => FIGfont::from_content(include_str!("../fonts/banner.flf")),
-            "block" => FIGfont::from_content(include_str!("../fonts/block.flf")),
-            _ => FIGfont::standard(),
-        };
+        grid
+    }
 
-        let font = font_result.unwrap_or_else(|_| {
-            // If all else fails, try to create a minimal font
-            FIGfont::from_content("").unwrap_or_else(|_| {
-                // This should never happen, but if it does, we'll panic
-                panic!("Failed to load any font")
-            })
-        });
+    /// Repeat rows, and columns from double size up, to enlarge the art.
+    /// Wide clusters are repeated whole, each with its continuation.
+    fn scale(&self, grid: CellGrid) -> CellGrid {
+        if self.size <= 1.0 {
+            return grid;
+        }
 
-        let figure = font.convert(text);
-        match figure {
-            Some(fig) => Ok(fig.to_string()),
-            None => Ok(text.to_string()), // Fallback to plain text
+        let vertical_repeat = self.size.round() as usize;
+        let horizontal_repeat = if self.size >= 2.0 { vertical_repeat } else { 1 };
+        let mut scaled = CellGrid::new();
+        for row in grid.rows() {
+            let mut wide: Vec<Cell> = Vec::new();
+            for (index, cell) in row.iter().enumerate() {
+                if cell.is_continuation() {
+                    continue;
+                }
+                let spills = row[index + 1..].iter().take_while(|next| next.is_continuation()).count();
+                for _ in 0..horizontal_repeat {
+                    wide.push(cell.clone());
+                    wide.extend(std::iter::repeat_n(Cell::continuation(cell.style), spills));
+                }
+            }
+            for _ in 0..vertical_repeat {
+                scaled.push_row(wide.clone());
+            }
         }
+        scaled
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::{FontMetrics, FontOptions};
+    use crate::figlet::LayoutMode;
+    use crate::fonts;
+    use crate::grid::Rgb;
+    use crate::themes::test_theme;
+
+    /// Draws every character as `[c]` on one row
+    struct FakeFont;
+
+    impl FontBackend for FakeFont {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn render(&self, text: &str) -> CellGrid {
+            let line: String = text.chars().map(|ch| format!("[{}]", ch)).collect();
+            CellGrid::from_lines(&[line], CellStyle::default())
+        }
 
-    fn create_test_theme() -> Theme {
-        Theme {
-            name: "test".to_string(),
-            description: "Test theme".to_string(),
-            foreground: "#ffffff".to_string(),
-            background: "#000000".to_string(),
-            accent: "#ff0000".to_string(),
-            strong: "#00ff00".to_string(),
-            dim: "#0000ff".to_string(),
-            ascii_art_style: "bold".to_string(),
+        fn metrics(&self) -> FontMetrics {
+            FontMetrics { height: 1 }
         }
+
+        fn supports(&self, _ch: char) -> bool {
+            true
+        }
+    }
+
+    fn standard() -> Box<dyn FontBackend> {
+        fonts::default_font().backend(&FontOptions::default()).unwrap()
     }
 
     #[test]
     fn test_renderer_creation() {
-        let theme = create_test_theme();
-        let renderer = Renderer::new(theme, "standard", 1.0);
-        assert_eq!(renderer.font_name, "standard");
+        let theme = test_theme();
+        let renderer = Renderer::new(theme, standard(), 1.0);
+        assert_eq!(renderer.font_name(), "standard");
     }
 
     #[test]
     fn test_render_figlet() {
-        let theme = create_test_theme();
-        let renderer = Renderer::new(theme, "standard", 1.0);
-        let result = renderer.render_figlet("TEST");
-        assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(!output.is_empty());
+        let theme = test_theme();
+        let renderer = Renderer::new(theme, standard(), 1.0);
+        let grid = renderer.art_grid("TEST");
+        assert!(grid.height() > 1);
+        assert!(grid.width() > 0);
+    }
+
+    #[test]
+    fn test_render_figlet_stacks_lines() {
+        let theme = test_theme();
+        let options = FontOptions {
+            layout: Some(LayoutMode::Full),
+            ..FontOptions::default()
+        };
+        let font = fonts::default_font().backend(&options).unwrap();
+        let renderer = Renderer::new(theme, font, 1.0);
+        let single = renderer.art_grid("A");
+        let stacked = renderer.art_grid("A\nA");
+        assert_eq!(stacked.height(), single.height() * 2);
+    }
+
+    #[test]
+    fn test_scaling_keeps_wide_clusters_whole() {
+        let theme = test_theme();
+        let renderer = Renderer::new(theme, Box::new(FakeFont), 2.0);
+        let grid = renderer.art_grid("日");
+        assert_eq!(grid.width(), 8);
+
+        let row = &grid.rows()[0];
+        let text: String = row.iter().map(|cell| cell.symbol.as_str()).collect();
+        assert_eq!(text, "[[日日]]");
+        assert!(row[3].is_continuation() && row[5].is_continuation());
+    }
+
+    #[test]
+    fn test_backend_art_is_styled_and_scaled() {
+        let theme = test_theme();
+        let renderer = Renderer::new(theme, Box::new(FakeFont), 2.0);
+        assert_eq!(renderer.font_name(), "fake");
+
+        let grid = renderer.art_grid("hi");
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.width(), 12);
+        let cell = &grid.rows()[1][2];
+        assert_eq!(cell.symbol, "h");
+        assert_eq!(cell.style, CellStyle::fg(Rgb::new(0, 255, 0)).bold());
+        assert_eq!(grid.background, Some(Rgb::new(0, 0, 0)));
     }
 }
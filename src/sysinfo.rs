@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// A piece of system information that can be shown in the info panel
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InfoField {
+    User,
+    Os,
+    Kernel,
+    Uptime,
+    Load,
+    Memory,
+    Disk,
+    Shell,
+    Terminal,
+}
+
+impl InfoField {
+    pub fn all() -> Vec<InfoField> {
+        vec![
+            InfoField::User,
+            InfoField::Os,
+            InfoField::Kernel,
+            InfoField::Uptime,
+            InfoField::Load,
+            InfoField::Memory,
+            InfoField::Disk,
+            InfoField::Shell,
+            InfoField::Terminal,
+        ]
+    }
+
+    /// Label shown before the value, or `None` for the `user@host` title line
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            InfoField::User => None,
+            InfoField::Os => Some("OS"),
+            InfoField::Kernel => Some("Kernel"),
+            InfoField::Uptime => Some("Uptime"),
+            InfoField::Load => Some("Load"),
+            InfoField::Memory => Some("Memory"),
+            InfoField::Disk => Some("Disk"),
+            InfoField::Shell => Some("Shell"),
+            InfoField::Terminal => Some("Terminal"),
+        }
+    }
+}
+
+/// Where the info panel goes relative to the art
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelLayout {
+    /// Beside the art when the terminal is wide enough, otherwise below
+    #[default]
+    Auto,
+    Side,
+    Below,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfoConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "InfoField::all")]
+    pub fields: Vec<InfoField>,
+    #[serde(default)]
+    pub layout: PanelLayout,
+}
+
+impl Default for InfoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fields: InfoField::all(),
+            layout: PanelLayout::default(),
+        }
+    }
+}
+
+/// A single collected line of the info panel
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfoLine {
+    pub label: Option<&'static str>,
+    pub value: String,
+}
+
+/// Collect the requested fields in order, skipping any that can't be read on
+/// this system
+pub fn collect(fields: &[InfoField]) -> Vec<InfoLine> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            read_field(*field).map(|value| InfoLine {
+                label: field.label(),
+                value,
+            })
+        })
+        .collect()
+}
+
+fn read_field(field: InfoField) -> Option<String> {
+    match field {
+        InfoField::User => {
+            let user = std::env::var("USER")
+                .or_else(|_| std::env::var("LOGNAME"))
+                .ok()?;
+            let host = read_trimmed("/proc/sys/kernel/hostname")?;
+            Some(format!("{}@{}", user, host))
+        }
+        InfoField::Os => parse_os_release(&fs::read_to_string("/etc/os-release").ok()?),
+        InfoField::Kernel => read_trimmed("/proc/sys/kernel/osrelease"),
+        InfoField::Uptime => {
+            let seconds = parse_uptime(&fs::read_to_string("/proc/uptime").ok()?)?;
+            Some(format_uptime(seconds))
+        }
+        InfoField::Load => {
+            let content = fs::read_to_string("/proc/loadavg").ok()?;
+            let load: Vec<&str> = content.split_whitespace().take(3).collect();
+            (load.len() == 3).then(|| load.join(" "))
+        }
+        InfoField::Memory => {
+            let (total, available) = parse_meminfo(&fs::read_to_string("/proc/meminfo").ok()?)?;
+            Some(format_usage(total.saturating_sub(available), total))
+        }
+        InfoField::Disk => {
+            let (total, free) = disk_usage(Path::new("/"))?;
+            Some(format_usage(total.saturating_sub(free), total))
+        }
+        InfoField::Shell => {
+            let shell = std::env::var("SHELL").ok()?;
+            Path::new(&shell)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        }
+        InfoField::Terminal => std::env::var("TERM_PROGRAM")
+            .or_else(|_| std::env::var("TERM"))
+            .ok(),
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+/// Extract a display name from /etc/os-release content
+fn parse_os_release(content: &str) -> Option<String> {
+    let mut name = None;
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "PRETTY_NAME" => return Some(value),
+                "NAME" => name = Some(value),
+                _ => {}
+            }
+        }
+    }
+    name
+}
+
+fn parse_uptime(content: &str) -> Option<u64> {
+    let seconds: f64 = content.split_whitespace().next()?.parse().ok()?;
+    Some(seconds as u64)
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let hours = (seconds % 86_400) / 3_600;
+    let minutes = (seconds % 3_600) / 60;
+
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(format!("{}d", days));
+    }
+    if hours > 0 {
+        parts.push(format!("{}h", hours));
+    }
+    parts.push(format!("{}m", minutes));
+    parts.join(" ")
+}
+
+/// Return (total, available) memory in bytes from /proc/meminfo content
+fn parse_meminfo(content: &str) -> Option<(u64, u64)> {
+    let mut total = None;
+    let mut available = None;
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let key = parts.next();
+        let kib: Option<u64> = parts.next().and_then(|v| v.parse().ok());
+        match key {
+            Some("MemTotal:") => total = kib,
+            Some("MemAvailable:") => available = kib,
+            _ => {}
+        }
+    }
+    Some((total? * 1024, available? * 1024))
+}
+
+/// Return (total, available) bytes for the filesystem containing `path`
+#[cfg(unix)]
+fn disk_usage(path: &Path) -> Option<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is a valid NUL-terminated string and stat is a valid
+    // out-pointer for the duration of the call
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some((stat.f_blocks as u64 * block, stat.f_bavail as u64 * block))
+}
+
+#[cfg(not(unix))]
+fn disk_usage(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+fn format_usage(used: u64, total: u64) -> String {
+    let percent = (used * 100).checked_div(total).unwrap_or(0);
+    format!("{} / {} ({}%)", format_bytes(used), format_bytes(total), percent)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_os_release() {
+        let content = "NAME=\"Arch Linux\"\nPRETTY_NAME=\"Arch Linux (rolling)\"\nID=arch\n";
+        assert_eq!(parse_os_release(content), Some("Arch Linux (rolling)".to_string()));
+        assert_eq!(parse_os_release("NAME=Debian\n"), Some("Debian".to_string()));
+    }
+
+    #[test]
+    fn test_uptime_formatting() {
+        assert_eq!(parse_uptime("93784.52 12345.67\n"), Some(93784));
+        assert_eq!(format_uptime(93784), "1d 2h 3m");
+        assert_eq!(format_uptime(59), "0m");
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let content = "MemTotal:       16384000 kB\nMemFree:  1000 kB\nMemAvailable:    8192000 kB\n";
+        assert_eq!(
+            parse_meminfo(content),
+            Some((16_384_000 * 1024, 8_192_000 * 1024))
+        );
+        assert_eq!(format_usage(512 * 1024 * 1024, 1024 * 1024 * 1024), "512.0 MiB / 1.0 GiB (50%)");
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::{Context, Result};
-use colored::Color;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::effects::FillStyle;
+use crate::grid::Rgb;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Theme {
@@ -15,6 +17,9 @@ pub struct Theme {
     pub dim: String,
     #[serde(default = "default_ascii_art_style")]
     pub ascii_art_style: String,
+    /// How the art is filled in, beyond the plain art color
+    #[serde(default)]
+    pub fill: FillStyle,
 }
 
 fn default_ascii_art_style() -> String {
@@ -22,17 +27,57 @@ fn default_ascii_art_style() -> String {
 }
 
 impl Theme {
-    pub fn get_foreground_color(&self) -> Color {
+    pub fn get_foreground_color(&self) -> Rgb {
         hex_to_color(&self.foreground)
     }
 
-    pub fn get_accent_color(&self) -> Color {
+    pub fn get_background_color(&self) -> Rgb {
+        hex_to_color(&self.background)
+    }
+
+    pub fn get_accent_color(&self) -> Rgb {
         hex_to_color(&self.accent)
     }
 
-    pub fn get_strong_color(&self) -> Color {
+    pub fn get_strong_color(&self) -> Rgb {
         hex_to_color(&self.strong)
     }
+
+    pub fn get_dim_color(&self) -> Rgb {
+        hex_to_color(&self.dim)
+    }
+
+    /// Check that every color is a valid #RRGGBB value. Rendering falls back
+    /// to white for bad colors, so this is the only place they get reported.
+    pub fn validate(&self) -> Result<()> {
+        let colors = [
+            ("foreground", &self.foreground),
+            ("background", &self.background),
+            ("accent", &self.accent),
+            ("strong", &self.strong),
+            ("dim", &self.dim),
+        ];
+        for (field, value) in colors {
+            if !is_hex_color(value) {
+                anyhow::bail!("Invalid color for {}: '{}' (expected #RRGGBB)", field, value);
+            }
+        }
+        if !["bold", "italic", "normal"].contains(&self.ascii_art_style.as_str()) {
+            anyhow::bail!(
+                "Invalid ascii_art_style: '{}' (expected bold, italic or normal)",
+                self.ascii_art_style
+            );
+        }
+        if let FillStyle::Pattern(pattern) = &self.fill {
+            if pattern.scale == 0 {
+                anyhow::bail!("A pattern fill's scale must be at least 1");
+            }
+            if let Some(color) = pattern.colors.iter().find(|color| !is_hex_color(color)) {
+                anyhow::bail!("Invalid fill color: '{}' (expected #RRGGBB)", color);
+            }
+        }
+        Ok(())
+    }
 }
 
 pub struct ThemeManager {
@@ -43,7 +88,7 @@ impl ThemeManager {
     pub fn new(themes_dir: PathBuf) -> Result<Self> {
         if !themes_dir.exists() {
             anyhow::bail!(
-                "Themes directory does not exist: {}. Run 'greetme -c' to create it.",
+                "Themes directory does not exist: {}. Run 'greetme init' to create it.",
                 themes_dir.display()
             );
         }
@@ -61,12 +106,64 @@ impl ThemeManager {
             anyhow::bail!("Theme '{}' not found at {}", name, path.display());
         }
 
-        let content = fs::read_to_string(&path)
-            .context(format!("Failed to read theme file: {}", path.display()))?;
-        let theme: Theme = toml::from_str(&content)
-            .context(format!("Failed to parse theme file: {}", path.display()))?;
+        load_theme_file(&path)
+    }
+
+    /// Validate a theme file and copy it into the themes directory under its
+    /// file stem. Returns the name it was installed as.
+    pub fn import_theme(&self, source: &Path, overwrite: bool) -> Result<String> {
+        let theme = load_theme_file(source)?;
+        theme.validate()?;
+
+        let name = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Invalid theme file name: {}", source.display()))?;
+        if name.contains("..") {
+            anyhow::bail!("Invalid theme name: {}", name);
+        }
+
+        let path = self.themes_dir.join(format!("{}.toml", name));
+        if path.exists() && !overwrite {
+            anyhow::bail!("Theme '{}' already exists. Use --force to overwrite.", name);
+        }
+
+        // A texture is read beside the theme file, so it is copied along
+        // and the theme is pointed at the copy
+        if let FillStyle::Texture(texture) = &theme.fill {
+            let image = source.parent().unwrap_or(Path::new(".")).join(&texture.image);
+            let file_name = image
+                .file_name()
+                .ok_or_else(|| anyhow::anyhow!("Invalid texture file name: {}", image.display()))?;
+            self.import_texture(&image, &self.themes_dir.join(file_name), overwrite)?;
+
+            if texture.image != Path::new(file_name) {
+                let mut table: toml::Value = toml::from_str(&fs::read_to_string(source)?)?;
+                table["fill"]["image"] = toml::Value::String(file_name.to_string_lossy().to_string());
+                fs::write(&path, toml::to_string_pretty(&table)?)
+                    .context(format!("Failed to import theme: {}", name))?;
+                return Ok(name);
+            }
+        }
+
+        fs::copy(source, &path).context(format!("Failed to import theme: {}", name))?;
+        Ok(name)
+    }
 
-        Ok(theme)
+    /// Copy a theme's texture into the themes directory, leaving an
+    /// identical copy that is already there alone
+    fn import_texture(&self, image: &Path, target: &Path, overwrite: bool) -> Result<()> {
+        let bytes = fs::read(image).context(format!("Failed to read texture: {}", image.display()))?;
+        if fs::read(target).is_ok_and(|existing| existing == bytes) {
+            return Ok(());
+        }
+        if target.exists() && !overwrite {
+            anyhow::bail!(
+                "Texture '{}' already exists in the themes directory. Use --force to overwrite.",
+                target.file_name().unwrap_or_default().to_string_lossy()
+            );
+        }
+        fs::write(target, bytes).context(format!("Failed to import texture: {}", image.display()))
     }
 
     pub fn list_themes(&self) -> Result<Vec<String>> {
@@ -94,20 +191,53 @@ impl ThemeManager {
     }
 }
 
-/// Convert hex color to colored::Color
+/// Load a theme from an arbitrary path
+pub fn load_theme_file(path: &Path) -> Result<Theme> {
+    let content = fs::read_to_string(path)
+        .context(format!("Failed to read theme file: {}", path.display()))?;
+    let mut theme: Theme = toml::from_str(&content)
+        .context(format!("Failed to parse theme file: {}", path.display()))?;
+    theme.fill.load(path.parent().unwrap_or(Path::new(".")))?;
+
+    Ok(theme)
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Convert hex color to Rgb
 /// Supports #RRGGBB format and falls back to white on parse errors
-fn hex_to_color(hex: &str) -> Color {
+pub fn hex_to_color(hex: &str) -> Rgb {
     let hex = hex.trim_start_matches('#');
     
-    if hex.len() != 6 {
-        return Color::White;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Rgb::WHITE;
     }
 
     let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
     let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
     let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
 
-    Color::TrueColor { r, g, b }
+    Rgb::new(r, g, b)
+}
+
+/// Plain theme shared by the unit tests of the render and export modules
+#[cfg(test)]
+pub(crate) fn test_theme() -> Theme {
+    Theme {
+        name: "test".to_string(),
+        description: "Test theme".to_string(),
+        foreground: "#ffffff".to_string(),
+        background: "#000000".to_string(),
+        accent: "#ff0000".to_string(),
+        strong: "#00ff00".to_string(),
+        dim: "#0000ff".to_string(),
+        ascii_art_style: "bold".to_string(),
+        fill: Default::default(),
+    }
 }
 
 #[cfg(test)]
@@ -118,14 +248,10 @@ mod tests {
     #[test]
     fn test_hex_to_color() {
         let color = hex_to_color("#ff0000");
-        match color {
-            Color::TrueColor { r, g, b } => {
-                assert_eq!(r, 255);
-                assert_eq!(g, 0);
-                assert_eq!(b, 0);
-            }
-            _ => panic!("Expected TrueColor"),
-        }
+        assert_eq!(color.r, 255);
+        assert_eq!(color.g, 0);
+        assert_eq!(color.b, 0);
+        assert_eq!(hex_to_color("red"), Rgb::WHITE);
     }
 
     #[test]
@@ -158,4 +284,45 @@ ascii_art_style = \"bold\"
         assert!(manager.load_theme("../evil").is_err());
         assert!(manager.load_theme("subdir/theme").is_err());
     }
+
+    #[test]
+    fn test_theme_validation() {
+        let mut theme: Theme = toml::from_str(include_str!("../themes/dracula.toml")).unwrap();
+        assert!(theme.validate().is_ok());
+
+        theme.accent = "cyan".to_string();
+        assert!(theme.validate().is_err());
+    }
+
+    #[test]
+    fn test_texture_fill_loads_beside_the_theme() {
+        let temp_dir = TempDir::new().unwrap();
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+            .save(temp_dir.path().join("marble.png"))
+            .unwrap();
+        let theme = format!(
+            "{}\n[fill]\nstyle = \"texture\"\nimage = \"marble.png\"\n",
+            include_str!("../themes/dracula.toml")
+        );
+        let path = temp_dir.path().join("marble.toml");
+        fs::write(&path, &theme).unwrap();
+        assert!(matches!(load_theme_file(&path).unwrap().fill, FillStyle::Texture(_)));
+
+        fs::remove_file(temp_dir.path().join("marble.png")).unwrap();
+        let error = load_theme_file(&path).unwrap_err().to_string();
+        assert!(error.contains("marble.png"));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_pattern_fill() {
+        let mut theme: Theme = toml::from_str(include_str!("../themes/dracula.toml")).unwrap();
+        theme.fill = toml::from_str("style = \"pattern\"\npattern = \"dots\"\ncolors = [\"#ff0000\", \"#0000ff\"]").unwrap();
+        assert!(theme.validate().is_ok());
+
+        theme.fill = toml::from_str("style = \"pattern\"\npattern = \"dots\"\ncolors = [\"#ff0000\", \"blue\"]").unwrap();
+        assert!(theme.validate().is_err());
+
+        theme.fill = toml::from_str("style = \"pattern\"\npattern = \"checkers\"\nscale = 0").unwrap();
+        assert!(theme.validate().is_err());
+    }
 }
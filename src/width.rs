@@ -0,0 +1,78 @@
+//! Display width of text: what the terminal draws is grapheme clusters, a
+//! character with its combining marks or a whole emoji sequence, each one
+//! or two columns wide.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Variation selector asking for emoji presentation, which terminals draw
+/// two columns wide
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+/// Columns a single grapheme cluster takes up
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.contains(EMOJI_PRESENTATION) {
+        return 2;
+    }
+    // Joined sequences are as wide as their widest part, not the sum
+    grapheme
+        .chars()
+        .filter_map(UnicodeWidthChar::width)
+        .max()
+        .unwrap_or(0)
+        .min(2)
+}
+
+/// Grapheme clusters of `text` with their widths. Zero-width clusters, such
+/// as a stray combining mark, stay with the cluster before them.
+pub fn graphemes(text: &str) -> Vec<(&str, usize)> {
+    let mut clusters: Vec<(&str, usize)> = Vec::new();
+    let mut start = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        let width = grapheme_width(grapheme);
+        let end = offset + grapheme.len();
+        match clusters.last_mut() {
+            Some((previous, _)) if width == 0 => *previous = &text[start..end],
+            _ => {
+                clusters.push((grapheme, width.max(1)));
+                start = offset;
+            }
+        }
+    }
+    clusters
+}
+
+/// Columns `text` takes up on a terminal
+pub fn width(text: &str) -> usize {
+    graphemes(text).iter().map(|(_, width)| width).sum()
+}
+
+/// Pad `text` with spaces to `columns` wide
+pub fn pad(text: &str, columns: usize) -> String {
+    format!("{}{}", text, " ".repeat(columns.saturating_sub(width(text))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combining_marks_join_their_base() {
+        // 'e' followed by a combining acute accent
+        let text = "Zoe\u{301}!";
+        assert_eq!(graphemes(text), vec![("Z", 1), ("o", 1), ("e\u{301}", 1), ("!", 1)]);
+        assert_eq!(width(text), 4);
+        assert_eq!(graphemes("\u{301}a"), vec![("\u{301}", 1), ("a", 1)]);
+    }
+
+    #[test]
+    fn test_wide_and_emoji_clusters() {
+        assert_eq!(width("日本"), 4);
+        assert_eq!(width("a😀"), 3);
+        // A family joined with zero-width joiners is one two-column cluster
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(graphemes(family), vec![(family, 2)]);
+        assert_eq!(width("❤\u{FE0F}"), 2);
+        assert_eq!(pad("日", 4), "日  ");
+    }
+}
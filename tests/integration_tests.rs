@@ -1,3 +1,6 @@
+// The original tests pass borrowed argument arrays
+#![allow(clippy::needless_borrows_for_generic_args)]
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
@@ -116,6 +119,9 @@ fn test_list_themes() {
     cmd2.arg("--list-themes");
     cmd2.assert()
         .success()
+        .stderr(predicate::str::contains(
+            "Warning: '--list-themes' is deprecated, use 'greetme theme list' instead.",
+        ))
         .stdout(predicate::str::contains("onedark"))
         .stdout(predicate::str::contains("solarized"))
         .stdout(predicate::str::contains("dracula"))
@@ -135,6 +141,21 @@ fn test_read_without_config_fails() {
         .stderr(predicate::str::contains("Failed to load config"));
 }
 
+#[test]
+fn test_read_reports_missing_config_before_tty() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Piped output would fail the TTY check, but the missing config is the
+    // problem worth reporting
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("read");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to load config"))
+        .stderr(predicate::str::contains("not a terminal").not());
+}
+
 #[test]
 fn test_display_text_saves_to_config() {
     let temp_dir = TempDir::new().unwrap();
@@ -176,6 +197,38 @@ fn test_invalid_theme_name_fails() {
         .stderr(predicate::str::contains("not found"));
 }
 
+#[test]
+fn test_missing_font_fails_unless_configured() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // A font named on the command line, by name or path, has to exist
+    let missing_path = temp_dir.path().join("Lat2-Terminus16.psf.gz");
+    for font in ["nosuchfont", missing_path.to_str().unwrap()] {
+        let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+        cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+        cmd2.args(["show", "Hi", "--format", "plain", "--font", font]);
+        cmd2.assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("Font '{}' not found", font)));
+    }
+
+    // A configured default that has gone missing still renders with standard
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["config", "set", "default_font", "nosuchfont"]);
+    cmd3.assert().success();
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["show", "Hi", "--format", "plain"]);
+    cmd4.assert().success().stdout(predicate::str::contains("|_|"));
+}
+
 #[test]
 fn test_path_traversal_prevented() {
     let temp_dir = TempDir::new().unwrap();
@@ -194,3 +247,867 @@ fn test_path_traversal_prevented() {
         .failure()
         .stderr(predicate::str::contains("Invalid theme name").or(predicate::str::contains("not found")));
 }
+
+#[test]
+fn test_add_list_remove_greetings() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("-c");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["add", "morning", "Good morning", "--theme", "dracula"]);
+    cmd2.assert().success();
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.arg("list");
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("morning: Good morning [theme=dracula]"));
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["remove", "morning"]);
+    cmd4.assert().success();
+
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["remove", "morning"]);
+    cmd5.assert()
+        .failure()
+        .stderr(predicate::str::contains("No greeting named"));
+}
+
+#[test]
+fn test_sequential_rotation_persists() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join("greetme");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("-c");
+    cmd.assert().success();
+
+    for (name, text) in [("first", "One"), ("second", "Two")] {
+        let mut add = Command::cargo_bin("greetme").unwrap();
+        add.env("XDG_CONFIG_HOME", temp_dir.path());
+        add.args(["add", name, text]);
+        add.assert().success();
+    }
+
+    // Each read advances the persisted rotation index
+    let mut read = Command::cargo_bin("greetme").unwrap();
+    read.env("XDG_CONFIG_HOME", temp_dir.path());
+    read.args(["-r", "--force"]);
+    read.assert().success();
+
+    let state = fs::read_to_string(config_dir.join("state.toml")).unwrap();
+    assert!(state.contains("next_index = 1"));
+
+    let mut read2 = Command::cargo_bin("greetme").unwrap();
+    read2.env("XDG_CONFIG_HOME", temp_dir.path());
+    read2.args(["-r", "--force"]);
+    read2.assert().success();
+
+    let state = fs::read_to_string(config_dir.join("state.toml")).unwrap();
+    assert!(state.contains("next_index = 0"));
+}
+
+#[test]
+fn test_quote_from_local_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let quote_file = temp_dir.path().join("quotes");
+    fs::write(&quote_file, "Stay curious\n%\nA much longer quote that the filter drops\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("-c");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["-t", "Hi", "--force", "--quote", "--quote-max-length", "20"]);
+    cmd2.arg("--quote-file").arg(&quote_file);
+    cmd2.assert()
+        .success()
+        .stdout(predicate::str::contains("Stay curious"))
+        .stdout(predicate::str::contains("filter drops").not());
+}
+
+#[test]
+fn test_read_text_from_stdin() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("-c");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["-", "--force"]);
+    cmd2.write_stdin("12:34\n");
+    cmd2.assert().success().stdout(predicate::str::is_empty().not());
+}
+
+#[test]
+fn test_read_text_from_file_respects_max_length() {
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().join("greeting.txt");
+    fs::write(&input, "Hello\nWorld\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("-c");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["--force", "--file"]).arg(&input);
+    cmd2.assert().success();
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["--force", "--max-input-length", "5", "--file"]).arg(&input);
+    cmd3.assert()
+        .failure()
+        .stderr(predicate::str::contains("longer than 5 characters"));
+}
+
+#[test]
+fn test_long_wide_input_reports_its_length() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("-c");
+    cmd.assert().success();
+
+    // Three bytes a character, so the read limit falls inside one
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["-", "--force"]);
+    cmd2.write_stdin("日".repeat(1500));
+    cmd2.assert()
+        .failure()
+        .stderr(predicate::str::contains("Input is longer than 1024 characters"));
+}
+
+#[test]
+fn test_legacy_flags_conflict() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.args(["-t", "hi", "--list-themes"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_subcommands() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join("greetme");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["theme", "set", "gruvbox"]);
+    cmd2.assert().success();
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["config", "get", "default_theme"]);
+    cmd3.assert().success().stdout("gruvbox\n");
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["config", "set", "info.layout", "below"]);
+    cmd4.assert().success();
+
+    let config_content = fs::read_to_string(config_dir.join("config.toml")).unwrap();
+    assert!(config_content.contains("layout = \"below\""));
+
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["show", "Hello", "--force", "--save"]);
+    cmd5.assert().success();
+
+    let mut cmd6 = Command::cargo_bin("greetme").unwrap();
+    cmd6.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd6.args(["config", "get", "last_shown"]);
+    cmd6.assert().success().stdout("Hello\n");
+}
+
+#[test]
+fn test_theme_import_and_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let theme_file = temp_dir.path().join("custom.toml");
+    fs::write(
+        &theme_file,
+        "name = \"custom\"\ndescription = \"Custom\"\nforeground = \"#ffffff\"\n\
+         background = \"#000000\"\naccent = \"#ff0000\"\nstrong = \"#00ff00\"\ndim = \"#0000ff\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["theme", "import"]).arg(&theme_file);
+    cmd2.assert().success();
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["theme", "check", "custom"]);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("is valid"));
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["theme", "import"]).arg(&theme_file);
+    cmd4.assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+}
+
+#[test]
+fn test_svg_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("banner.svg");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["config", "set", "export.svg.font_family", "Fira Code"]);
+    cmd2.assert().success();
+
+    // No --force needed: nothing is written to the terminal
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--output"]).arg(&output);
+    cmd3.assert().success().stdout(predicate::str::is_empty());
+
+    let svg = fs::read_to_string(&output).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("font-family=\"Fira Code\""));
+    assert!(svg.contains("fill=\"#282c34\""));
+}
+
+#[test]
+fn test_png_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("banner.png");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--output"]).arg(&output);
+    cmd2.assert().success().stdout(predicate::str::is_empty());
+
+    let png = fs::read(&output).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--output"])
+        .arg(temp_dir.path().join("banner.bmp"));
+    cmd3.assert()
+        .failure()
+        .stderr(predicate::str::contains("Supported extensions: .svg, .png"));
+}
+
+#[test]
+fn test_html_output() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // --format writes to stdout without needing a terminal
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--format", "html"]);
+    cmd2.assert()
+        .success()
+        .stdout(predicate::str::starts_with("<pre class=\"greetme\""))
+        .stdout(predicate::str::contains("<span style=\"color:#"));
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["config", "set", "export.html.css_classes", "true"]);
+    cmd3.assert().success();
+
+    let output = temp_dir.path().join("banner.html");
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["show", "Hi", "-o"]).arg(&output);
+    cmd4.assert().success();
+
+    let html = fs::read_to_string(&output).unwrap();
+    assert!(html.contains("var(--greetme-accent"));
+    assert!(html.contains("class=\"greetme-fg-"));
+}
+
+#[test]
+fn test_json_output() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--font", "small", "--format", "json"]);
+    let output = cmd2.assert().success().get_output().stdout.clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["theme"], "onedark");
+    assert_eq!(json["font"], "small");
+    assert_eq!(json["height"].as_u64().unwrap() as usize, json["rows"].as_array().unwrap().len());
+    assert!(json["rows"][0][0]["char"].is_string());
+}
+
+#[test]
+fn test_plain_and_ansi_output() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.arg("show").arg("Hi");
+    cmd2.assert()
+        .failure()
+        .stderr(predicate::str::contains("--format plain"));
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--format", "plain"]);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("|_|").and(predicate::str::contains("\x1b[").not()));
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["show", "Hi", "--format", "ansi"]);
+    cmd4.assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[1;38;2;"));
+
+    let output = temp_dir.path().join("banner.txt");
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["show", "Hi", "-o"]).arg(&output);
+    cmd5.assert().success().stdout(predicate::str::is_empty());
+
+    let text = fs::read_to_string(&output).unwrap();
+    assert!(text.contains("|_|"));
+    assert!(!text.contains('\x1b'));
+}
+
+#[test]
+fn test_motd_banners() {
+    let temp_dir = TempDir::new().unwrap();
+    let motd = temp_dir.path().join("motd");
+    let issue = temp_dir.path().join("issue");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["motd", "Hi", "-o"]).arg(&motd);
+    cmd2.assert().success();
+    let banner = fs::read_to_string(&motd).unwrap();
+    assert!(banner.contains("|_|"));
+    assert!(!banner.contains('\x1b'));
+
+    // Dry runs diff against the file and leave it alone
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["motd", "Yo", "--dry-run", "-o"]).arg(&motd);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("+++").and(predicate::str::contains("\n-")));
+    assert_eq!(fs::read_to_string(&motd).unwrap(), banner);
+
+    // Issue targets escape the art's backslashes but not footer escapes
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["motd", "W", "--footer", "\\n on \\l", "-o"]).arg(&issue);
+    cmd4.assert().success();
+    let content = fs::read_to_string(&issue).unwrap();
+    assert!(content.contains("\\\\"));
+    assert!(content.ends_with("\\n on \\l\n"));
+
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["motd", "Hi", "--script"]);
+    cmd5.assert()
+        .success()
+        .stdout(predicate::str::contains("|_|"));
+}
+
+#[test]
+fn test_record_animation_to_asciicast() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("out.cast");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // Recording needs no terminal and doesn't print the greeting
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--animate", "rainbow", "--speed", "50", "--record"])
+        .arg(&output);
+    cmd2.assert().success().stdout(predicate::str::is_empty());
+
+    let cast = fs::read_to_string(&output).unwrap();
+    let mut lines = cast.lines();
+    let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(header["version"], 2);
+
+    let events: Vec<serde_json::Value> = lines.map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(events.len(), 51);
+    assert_eq!(events[1][0], 0.05);
+    assert_eq!(events[1][1], "o");
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--record"]).arg(&output);
+    cmd3.assert()
+        .failure()
+        .stderr(predicate::str::contains("--animate"));
+}
+
+#[test]
+fn test_gif_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let animated = temp_dir.path().join("greeting.gif");
+    let still = temp_dir.path().join("still.gif");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--animate", "fade", "-o"]).arg(&animated);
+    cmd2.assert().success().stdout(predicate::str::is_empty());
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "-o"]).arg(&still);
+    cmd3.assert().success();
+
+    let animated = fs::read(&animated).unwrap();
+    let still = fs::read(&still).unwrap();
+    assert!(animated.starts_with(b"GIF89a"));
+    assert!(still.starts_with(b"GIF89a"));
+    assert!(animated.len() > still.len());
+}
+
+#[test]
+fn test_font_list_and_preview() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // init writes editable copies of the built-in fonts, which then take precedence
+    let fonts_dir = temp_dir.path().join("greetme").join("fonts");
+    let chrome = std::fs::read_to_string(fonts_dir.join("chrome.flf")).unwrap();
+    assert!(chrome.starts_with("flf2a$"));
+    assert!(!fonts_dir.join("standard.txt").exists());
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["font", "list"]);
+    cmd2.assert()
+        .success()
+        .stdout(predicate::str::contains("pixel"))
+        .stdout(predicate::str::contains("small.flf"))
+        .stdout(predicate::str::contains("Small by Glenn Chappell"));
+
+    // Not a terminal, so every font is printed without paging
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["font", "preview", "Hi"]);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("standard ("))
+        .stdout(predicate::str::contains("chrome ("))
+        .stdout(predicate::str::contains("Failed").not());
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["font", "preview", "Hi", "--font", "banner"]);
+    cmd4.assert()
+        .success()
+        .stdout(predicate::str::contains("banner.flf)"))
+        .stdout(predicate::str::contains("standard").not());
+
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["font", "preview", "Hi Вася", "--font", "small"]);
+    cmd5.assert()
+        .success()
+        .stdout(predicate::str::contains("No glyphs for: Вася"));
+}
+
+#[test]
+fn test_layout_modes() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // The standard font smushes by default, like figlet
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--format", "plain"]);
+    cmd2.assert()
+        .success()
+        .stdout(predicate::str::contains("|_| |_|_|"));
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--format", "plain", "--layout", "full"]);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains(" |_| |_| |_|"));
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["show", "Hi", "--format", "plain", "--layout", "kerning"]);
+    cmd4.assert()
+        .success()
+        .stdout(predicate::str::contains("|_| |_||_|"));
+
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["show", "Hi", "--layout", "squash"]);
+    cmd5.assert().failure();
+}
+
+#[test]
+fn test_toilet_font() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // Two-row blocks for 'O' and 'K', blanks for everything else
+    let mut tlf = "tlf2a$ 2 2 4 -1 1\nblocky\n".to_string();
+    for code in (32..127).chain([196, 214, 220, 228, 246, 252, 223]) {
+        tlf.push_str(match char::from_u32(code).unwrap() {
+            'O' => "▛▜@\n▙▟@@\n",
+            'K' => "▌▞@\n▌▚@@\n",
+            _ => "$$@\n$$@@\n",
+        });
+    }
+    fs::write(temp_dir.path().join("greetme/fonts/blocky.tlf"), tlf).unwrap();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "OK", "--font", "blocky", "--format", "plain"]);
+    cmd2.assert()
+        .success()
+        .stdout("▛▜▌▞\n▙▟▌▚\n");
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["font", "list"]);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("blocky.tlf"));
+}
+
+#[test]
+fn test_bitmap_font() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // A 2x4 'I' bar and an 'o' ring in a BDF font
+    let bdf = "STARTFONT 2.1\nFONT -test-dots\nFONTBOUNDINGBOX 2 4 0 0\n\
+        STARTPROPERTIES 1\nCOPYRIGHT \"Dots by me\"\nENDPROPERTIES\nCHARS 2\n\
+        STARTCHAR I\nENCODING 73\nDWIDTH 2 0\nBBX 2 4 0 0\nBITMAP\n40\n40\n40\n40\nENDCHAR\n\
+        STARTCHAR o\nENCODING 111\nDWIDTH 2 0\nBBX 2 2 0 0\nBITMAP\nC0\nC0\nENDCHAR\nENDFONT\n";
+    let font_path = temp_dir.path().join("greetme/fonts/dots.bdf");
+    fs::write(&font_path, bdf).unwrap();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Io", "--font", "dots", "--pixel", "braille", "--format", "plain"]);
+    cmd2.assert().success().stdout("⢸⣤\n");
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Io", "--format", "plain", "--font"]);
+    cmd3.arg(&font_path);
+    cmd3.assert().success().stdout(" █\n ███\n");
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["font", "list"]);
+    cmd4.assert()
+        .success()
+        .stdout(predicate::str::contains("dots.bdf"))
+        .stdout(predicate::str::contains("Dots by me"));
+}
+
+#[test]
+fn test_font_fallback() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // The primary font only has 'I'; the fallback font only has 'П'
+    let font = |name: &str, code: u32, bitmap: &str| {
+        format!(
+            "STARTFONT 2.1\nFONTBOUNDINGBOX 3 4 0 0\nCHARS 1\n\
+             STARTCHAR {}\nENCODING {}\nDWIDTH 3 0\nBBX 3 4 0 0\nBITMAP\n{}\nENDCHAR\nENDFONT\n",
+            name, code, bitmap
+        )
+    };
+    let fonts_dir = temp_dir.path().join("greetme/fonts");
+    fs::write(fonts_dir.join("latin.bdf"), font("I", 73, "40\n40\n40\n40")).unwrap();
+    fs::write(fonts_dir.join("cyrillic.bdf"), font("Pe", 1055, "E0\nA0\nA0\nA0")).unwrap();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["config", "set", "font_fallback", "[\"cyrillic\"]"]);
+    cmd2.assert().success();
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "IП?", "--font", "latin", "--format", "plain"]);
+    cmd3.assert()
+        .success()
+        .stdout(" █ █▀█┌?┐\n █ █ █└─┘\n");
+
+    // Fallbacks that are missing or fail to parse are skipped with a warning
+    fs::write(fonts_dir.join("broken.bdf"), "STARTFONT 2.1\n").unwrap();
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["config", "set", "font_fallback", "[\"broken\", \"nowhere\", \"cyrillic\"]"]);
+    cmd4.assert().success();
+
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["show", "IП?", "--font", "latin", "--format", "plain"]);
+    cmd5.assert()
+        .success()
+        .stdout(" █ █▀█┌?┐\n █ █ █└─┘\n")
+        .stderr(predicate::str::contains("skipping fallback font 'broken'"))
+        .stderr(predicate::str::contains("skipping fallback font 'nowhere': font not found"));
+}
+
+#[test]
+fn test_image_above_and_beside_greeting() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let logo = temp_dir.path().join("logo.png");
+    image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 255, 255, 255]))
+        .save(&logo)
+        .unwrap();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["show", "Hi", "--image-mode", "braille", "--format", "plain", "--image"]);
+    cmd2.arg(&logo);
+    cmd2.assert()
+        .success()
+        .stdout(predicate::str::starts_with("⣿⣿\n _   _ _\n"));
+
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--image-position", "beside", "--format", "plain", "--image"]);
+    cmd3.arg(&logo);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::starts_with("▀▀▀▀    _   _ _\n▀▀▀▀   | | | (_)\n       | |_| | |\n"));
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["show", "Hi", "--format", "plain", "--image", "missing.png"]);
+    cmd4.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to load image missing.png"));
+}
+
+#[test]
+fn test_theme_texture_fill() {
+    let temp_dir = TempDir::new().unwrap();
+    let themes_dir = temp_dir.path().join("greetme/themes");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+        .save(themes_dir.join("red.png"))
+        .unwrap();
+    let dracula = fs::read_to_string(themes_dir.join("dracula.toml")).unwrap();
+    let textured = dracula.replace("name = \"dracula\"", "name = \"textured\"")
+        + "\n[fill]\nstyle = \"texture\"\nimage = \"red.png\"\nmode = \"tile\"\n";
+    fs::write(themes_dir.join("textured.toml"), textured).unwrap();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["--set-theme", "textured"]);
+    cmd2.assert().success();
+
+    // The lettering takes the texture's red instead of dracula's pink
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["show", "Hi", "--format", "ansi"]);
+    cmd3.assert()
+        .success()
+        .stdout(predicate::str::contains("38;2;255;0;0m|_|"))
+        .stdout(predicate::str::contains("38;2;255;121;198m|").not());
+}
+
+#[test]
+fn test_textured_theme_import_and_set() {
+    let temp_dir = TempDir::new().unwrap();
+    let themes_dir = temp_dir.path().join("greetme/themes");
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    // The texture sits in a folder next to the theme being imported
+    let source_dir = temp_dir.path().join("download");
+    fs::create_dir_all(source_dir.join("textures")).unwrap();
+    image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+        .save(source_dir.join("textures/red.png"))
+        .unwrap();
+    let dracula = fs::read_to_string(themes_dir.join("dracula.toml")).unwrap();
+    let textured = dracula.replace("name = \"dracula\"", "name = \"tex\"")
+        + "\n[fill]\nstyle = \"texture\"\nimage = \"textures/red.png\"\n";
+    fs::write(source_dir.join("tex.toml"), textured).unwrap();
+
+    let mut cmd2 = Command::cargo_bin("greetme").unwrap();
+    cmd2.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd2.args(["theme", "import"]).arg(source_dir.join("tex.toml"));
+    cmd2.assert().success();
+    assert!(themes_dir.join("red.png").is_file());
+
+    // The imported theme keeps working once the download is gone
+    fs::remove_dir_all(&source_dir).unwrap();
+    let mut cmd3 = Command::cargo_bin("greetme").unwrap();
+    cmd3.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd3.args(["theme", "set", "tex"]);
+    cmd3.assert().success();
+
+    let mut cmd4 = Command::cargo_bin("greetme").unwrap();
+    cmd4.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd4.args(["show", "Hi", "--format", "ansi"]);
+    cmd4.assert()
+        .success()
+        .stdout(predicate::str::contains("38;2;255;0;0m|_|"));
+
+    // A texture that can't be read is reported as such, not as a missing theme
+    fs::remove_file(themes_dir.join("red.png")).unwrap();
+    let mut cmd5 = Command::cargo_bin("greetme").unwrap();
+    cmd5.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd5.args(["theme", "set", "tex"]);
+    cmd5.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to load image"))
+        .stderr(predicate::str::contains("not found").not());
+}
+
+#[test]
+fn test_image_protocols_write_escape_streams() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("greetme").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let logo = temp_dir.path().join("logo.png");
+    image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 255, 255, 255]))
+        .save(&logo)
+        .unwrap();
+
+    let show = |protocol: &str, format: &str| {
+        let mut cmd = Command::cargo_bin("greetme").unwrap();
+        cmd.env("XDG_CONFIG_HOME", temp_dir.path());
+        cmd.args(["show", "Hi", "--format", format, "--image-protocol", protocol, "--image"]);
+        cmd.arg(&logo);
+        cmd.assert().success()
+    };
+
+    // A pipe reports no cell size, so the image is one 10x20 pixel cell
+    // above the six rows of art; the escapes go back up and draw it there
+    show("sixel", "ansi").stdout(predicate::str::ends_with(
+        "\x1b7\x1b[7A\r\x1bP0;1;0q\"1;1;10;10#215;2;100;100;100#215!10~-#215!10N\x1b\\\x1b8",
+    ));
+    show("kitty", "ansi").stdout(predicate::str::contains(
+        "\x1b7\x1b[7A\r\x1b_Ga=T,f=100,c=1,C=1,q=2,m=0;iVBORw0KGgo",
+    ));
+    show("iterm2", "ansi").stdout(predicate::str::is_match(
+        "\x1b\\]1337;File=inline=1;size=[0-9]+;width=1;preserveAspectRatio=1:iVBORw0KGgo[A-Za-z0-9+/=]+\x07\x1b8$",
+    ).unwrap());
+
+    // Other formats can't carry the escapes and draw the image with characters
+    show("kitty", "plain").stdout(predicate::str::starts_with("▀▀▀▀\n▀▀▀▀\n _   _ _\n"));
+}